@@ -0,0 +1,495 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// Integer without a size limit, stored as its sign and magnitude.
+///
+/// The magnitude is a list of base 2^32 digits with the least significant one first.
+/// It never has leading zeros, so zero is an empty list and never negative
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+	negative: bool,
+	digits: Vec<u32>,
+}
+
+/// Results with more bits than this are rejected instead of exhausting the memory
+pub const MAX_BITS: u64 = 1 << 20;
+
+impl BigInt {
+	fn new(negative: bool, mut digits: Vec<u32>) -> Self {
+		while digits.last() == Some(&0) {
+			digits.pop();
+		}
+		Self {
+			negative: negative && !digits.is_empty(),
+			digits,
+		}
+	}
+
+	/// Expects `digits` to only contain valid digits of the radix
+	pub fn parse(digits: &str, radix: u32) -> Self {
+		let mut result = Vec::new();
+		for digit in digits.chars() {
+			let digit = digit
+				.to_digit(radix)
+				.expect("Only called with valid digits");
+			let mut carry = digit as u64;
+			for value in &mut result {
+				let product = *value as u64 * radix as u64 + carry;
+				*value = product as u32;
+				carry = product >> 32;
+			}
+			if carry != 0 {
+				result.push(carry as u32);
+			}
+		}
+		Self::new(false, result)
+	}
+
+	/// Converts a whole float, `None` for fractions, infinity and `NaN`
+	pub fn from_f64(value: f64) -> Option<Self> {
+		if !value.is_finite() || value.fract() != 0.0 {
+			return None;
+		}
+		let bits = value.to_bits();
+		let exponent = ((bits >> 52) & 0x7ff) as i64 - 1075;
+		let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+		// Whole floats below 2^53 are exact integers already
+		if exponent <= 0 {
+			return Some(Self::from(value as i64));
+		}
+		let magnitude = &Self::from(mantissa as i64) * &Self::from(2).pow(exponent as u32)?;
+		Some(if value < 0.0 { -&magnitude } else { magnitude })
+	}
+
+	pub fn to_i64(&self) -> Option<i64> {
+		let magnitude = match self.digits[..] {
+			[] => 0,
+			[low] => low as u64,
+			[low, high] => (high as u64) << 32 | low as u64,
+			_ => return None,
+		};
+		match self.negative {
+			false => i64::try_from(magnitude).ok(),
+			true if magnitude <= i64::MIN.unsigned_abs() => Some((magnitude as i64).wrapping_neg()),
+			true => None,
+		}
+	}
+
+	/// Rounds to the nearest float, values that are too large become infinity
+	pub fn to_f64(&self) -> f64 {
+		let magnitude =
+			(self.digits.iter().rev()).fold(0.0, |acc, digit| acc * 4294967296.0 + *digit as f64);
+		if self.negative {
+			-magnitude
+		} else {
+			magnitude
+		}
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.digits.is_empty()
+	}
+
+	pub fn is_negative(&self) -> bool {
+		self.negative
+	}
+
+	pub fn is_too_large(&self) -> bool {
+		self.bits() > MAX_BITS
+	}
+
+	pub fn bits(&self) -> u64 {
+		match self.digits.last() {
+			Some(last) => self.digits.len() as u64 * 32 - last.leading_zeros() as u64,
+			None => 0,
+		}
+	}
+
+	/// Quotient rounded towards zero and the remainder with the sign of `self`,
+	/// like `/` and `%` on primitive integers. `None` when dividing by zero
+	pub fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+		if other.is_zero() {
+			return None;
+		}
+		let (quotient, remainder) = div_rem_magnitude(&self.digits, &other.digits);
+		Some((
+			Self::new(self.negative != other.negative, quotient),
+			Self::new(self.negative, remainder),
+		))
+	}
+
+	/// Quotient rounded towards negative infinity
+	pub fn floor_div(&self, other: &Self) -> Option<Self> {
+		let (quotient, remainder) = self.div_rem(other)?;
+		if !remainder.is_zero() && self.negative != other.negative {
+			Some(&quotient - &Self::from(1))
+		} else {
+			Some(quotient)
+		}
+	}
+
+	/// `None` if the product certainly has more than `MAX_BITS` bits, which is checked before
+	/// multiplying because that takes long for huge numbers
+	pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+		// The product of an `n` and an `m` bit number has at least `n + m - 1` bits
+		(self.bits() + other.bits() <= MAX_BITS + 1).then(|| self * other)
+	}
+
+	/// `None` if the result has more than `MAX_BITS` bits
+	pub fn pow(&self, mut exponent: u32) -> Option<Self> {
+		// A base with `n` bits is at least 2^(n - 1), so the result has more than `(n - 1) * exponent`
+		if self.bits() > 1 && (self.bits() - 1).saturating_mul(exponent as u64) > MAX_BITS {
+			return None;
+		}
+		let mut base = self.clone();
+		let mut result = Self::from(1);
+		while exponent > 0 {
+			if exponent & 1 == 1 {
+				result = &result * &base;
+			}
+			exponent >>= 1;
+			if exponent > 0 {
+				base = &base * &base;
+			}
+		}
+		(!result.is_too_large()).then_some(result)
+	}
+}
+
+impl From<i64> for BigInt {
+	fn from(value: i64) -> Self {
+		let magnitude = value.unsigned_abs();
+		Self::new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+	}
+}
+
+impl Add for &BigInt {
+	type Output = BigInt;
+
+	fn add(self, other: &BigInt) -> BigInt {
+		if self.negative == other.negative {
+			return BigInt::new(self.negative, add_magnitude(&self.digits, &other.digits));
+		}
+		// Different signs, so the smaller magnitude is taken from the larger one
+		match cmp_magnitude(&self.digits, &other.digits) {
+			Ordering::Less => BigInt::new(other.negative, sub_magnitude(&other.digits, &self.digits)),
+			_ => BigInt::new(self.negative, sub_magnitude(&self.digits, &other.digits)),
+		}
+	}
+}
+
+impl Sub for &BigInt {
+	type Output = BigInt;
+
+	fn sub(self, other: &BigInt) -> BigInt {
+		self + &-other
+	}
+}
+
+impl Mul for &BigInt {
+	type Output = BigInt;
+
+	fn mul(self, other: &BigInt) -> BigInt {
+		let mut result = vec![0u32; self.digits.len() + other.digits.len()];
+		for (i, left) in self.digits.iter().enumerate() {
+			let mut carry = 0u64;
+			for (j, right) in other.digits.iter().enumerate() {
+				let product = *left as u64 * *right as u64 + result[i + j] as u64 + carry;
+				result[i + j] = product as u32;
+				carry = product >> 32;
+			}
+			result[i + other.digits.len()] = carry as u32;
+		}
+		BigInt::new(self.negative != other.negative, result)
+	}
+}
+
+impl Neg for &BigInt {
+	type Output = BigInt;
+
+	fn neg(self) -> BigInt {
+		BigInt::new(!self.negative, self.digits.clone())
+	}
+}
+
+impl PartialOrd for BigInt {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for BigInt {
+	fn cmp(&self, other: &Self) -> Ordering {
+		match (self.negative, other.negative) {
+			(false, true) => Ordering::Greater,
+			(true, false) => Ordering::Less,
+			(false, false) => cmp_magnitude(&self.digits, &other.digits),
+			(true, true) => cmp_magnitude(&other.digits, &self.digits),
+		}
+	}
+}
+
+impl Display for BigInt {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		// Split off nine decimal digits at a time, least significant chunk first
+		let mut chunks = Vec::new();
+		let mut digits = self.digits.clone();
+		while !digits.is_empty() {
+			chunks.push(div_rem_small(&mut digits, 1_000_000_000));
+		}
+
+		if self.negative {
+			write!(f, "-")?;
+		}
+		match chunks.split_last() {
+			Some((first, rest)) => {
+				write!(f, "{first}")?;
+				rest
+					.iter()
+					.rev()
+					.try_for_each(|chunk| write!(f, "{chunk:09}"))
+			}
+			None => write!(f, "0"),
+		}
+	}
+}
+
+fn cmp_magnitude(left: &[u32], right: &[u32]) -> Ordering {
+	left
+		.len()
+		.cmp(&right.len())
+		.then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+	let (long, short) = if left.len() >= right.len() {
+		(left, right)
+	} else {
+		(right, left)
+	};
+	let mut result = Vec::with_capacity(long.len() + 1);
+	let mut carry = 0u64;
+	for (i, digit) in long.iter().enumerate() {
+		let sum = *digit as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+		result.push(sum as u32);
+		carry = sum >> 32;
+	}
+	result.push(carry as u32);
+	result
+}
+
+/// Expects `left` to be at least as large as `right`
+fn sub_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+	let mut result = Vec::with_capacity(left.len());
+	let mut borrow = false;
+	for (i, digit) in left.iter().enumerate() {
+		let (value, overflow_a) = digit.overflowing_sub(*right.get(i).unwrap_or(&0));
+		let (value, overflow_b) = value.overflowing_sub(borrow as u32);
+		result.push(value);
+		borrow = overflow_a || overflow_b;
+	}
+	result
+}
+
+/// Divides `digits` in place and returns the remainder, trailing zeros are removed
+fn div_rem_small(digits: &mut Vec<u32>, divisor: u32) -> u32 {
+	let mut remainder = 0u64;
+	for digit in digits.iter_mut().rev() {
+		let value = remainder << 32 | *digit as u64;
+		*digit = (value / divisor as u64) as u32;
+		remainder = value % divisor as u64;
+	}
+	while digits.last() == Some(&0) {
+		digits.pop();
+	}
+	remainder as u32
+}
+
+/// Long division of magnitudes, algorithm D from Knuth's TAOCP volume 2, section 4.3.1
+fn div_rem_magnitude(left: &[u32], right: &[u32]) -> (Vec<u32>, Vec<u32>) {
+	if cmp_magnitude(left, right) == Ordering::Less {
+		return (Vec::new(), left.to_vec());
+	}
+	if let [divisor] = right {
+		let mut quotient = left.to_vec();
+		let remainder = div_rem_small(&mut quotient, *divisor);
+		return (quotient, vec![remainder]);
+	}
+
+	// Shift both so the top digit of the divisor has its highest bit set,
+	// which keeps the estimated quotient digits off by at most two
+	let shift = right.last().unwrap().leading_zeros();
+	let divisor = shift_left(right, shift);
+	let divisor = &divisor[..right.len()];
+	let mut rest = shift_left(left, shift);
+
+	let n = divisor.len();
+	let mut quotient = vec![0u32; rest.len() - n];
+	for j in (0..quotient.len()).rev() {
+		let top = (rest[j + n] as u64) << 32 | rest[j + n - 1] as u64;
+		let mut estimate = top / divisor[n - 1] as u64;
+		let mut remainder = top % divisor[n - 1] as u64;
+		while estimate >> 32 != 0
+			|| estimate * divisor[n - 2] as u64 > (remainder << 32 | rest[j + n - 2] as u64)
+		{
+			estimate -= 1;
+			remainder += divisor[n - 1] as u64;
+			if remainder >> 32 != 0 {
+				break;
+			}
+		}
+
+		// Subtract `estimate * divisor` from the current part of the rest
+		let mut borrow = 0i64;
+		let mut carry = 0u64;
+		for i in 0..n {
+			let product = estimate * divisor[i] as u64 + carry;
+			carry = product >> 32;
+			let value = rest[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+			rest[i + j] = value as u32;
+			borrow = (value < 0) as i64;
+		}
+		let value = rest[j + n] as i64 - borrow - carry as i64;
+		rest[j + n] = value as u32;
+
+		// The estimate was one too large, so the divisor is added back once
+		if value < 0 {
+			estimate -= 1;
+			let mut carry = 0u64;
+			for i in 0..n {
+				let sum = rest[i + j] as u64 + divisor[i] as u64 + carry;
+				rest[i + j] = sum as u32;
+				carry = sum >> 32;
+			}
+			rest[j + n] = rest[j + n].wrapping_add(carry as u32);
+		}
+		quotient[j] = estimate as u32;
+	}
+
+	(quotient, shift_right(&rest[..n], shift))
+}
+
+/// Always adds one digit for the bits shifted out of the top
+fn shift_left(digits: &[u32], shift: u32) -> Vec<u32> {
+	let mut result = Vec::with_capacity(digits.len() + 1);
+	let mut carry = 0;
+	for digit in digits {
+		result.push(digit << shift | carry);
+		carry = if shift == 0 { 0 } else { digit >> (32 - shift) };
+	}
+	result.push(carry);
+	result
+}
+
+fn shift_right(digits: &[u32], shift: u32) -> Vec<u32> {
+	(0..digits.len())
+		.map(|i| match (shift, digits.get(i + 1)) {
+			(0, _) => digits[i],
+			(_, Some(next)) => digits[i] >> shift | next << (32 - shift),
+			(_, None) => digits[i] >> shift,
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn big(value: &str) -> BigInt {
+		match value.strip_prefix('-') {
+			Some(digits) => -&BigInt::parse(digits, 10),
+			None => BigInt::parse(value, 10),
+		}
+	}
+
+	fn div_rem(left: &str, right: &str) -> (String, String) {
+		let (quotient, remainder) = big(left).div_rem(&big(right)).unwrap();
+		(quotient.to_string(), remainder.to_string())
+	}
+
+	#[test]
+	fn div_rem_truncates() {
+		assert_eq!(div_rem("7", "2"), ("3".into(), "1".into()));
+		assert_eq!(div_rem("-7", "2"), ("-3".into(), "-1".into()));
+		assert_eq!(div_rem("7", "-2"), ("-3".into(), "1".into()));
+		assert_eq!(
+			div_rem("1", "123456789012345678901"),
+			("0".into(), "1".into())
+		);
+		assert_eq!(
+			div_rem("-123456789012345678901234567890", "987654321"),
+			("-124999998873437499901".into(), "-574845669".into())
+		);
+		assert_eq!(
+			div_rem("123456789012345678901234567890", "-98765432109876543210"),
+			("-1249999988".into(), "60185185207253086410".into())
+		);
+		assert_eq!(
+			div_rem("-18446744073709551616", "4294967296"),
+			("-4294967296".into(), "0".into())
+		);
+	}
+
+	/// The first estimated quotient digit is one too large, so the divisor has to be added back
+	#[test]
+	fn div_rem_adds_back() {
+		assert_eq!(
+			div_rem(
+				"170141183420855150474555134919112130560",
+				"39614081257132168796771975169"
+			),
+			("4294967294".into(), "39614081257132168792477007874".into())
+		);
+	}
+
+	#[test]
+	fn div_rem_by_zero() {
+		assert_eq!(big("5").div_rem(&big("0")), None);
+		assert_eq!(big("5").floor_div(&big("0")), None);
+	}
+
+	#[test]
+	fn floor_div_rounds_down() {
+		let floor_div = |left: &str, right: &str| big(left).floor_div(&big(right)).unwrap().to_string();
+		assert_eq!(floor_div("7", "2"), "3");
+		assert_eq!(floor_div("-7", "2"), "-4");
+		assert_eq!(floor_div("7", "-2"), "-4");
+		assert_eq!(floor_div("-7", "-2"), "3");
+		assert_eq!(floor_div("-8", "2"), "-4");
+		assert_eq!(
+			floor_div("-123456789012345678901234567890", "987654321"),
+			"-124999998873437499902"
+		);
+	}
+
+	#[test]
+	fn to_i64_edges() {
+		assert_eq!(BigInt::from(i64::MAX).to_i64(), Some(i64::MAX));
+		assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+		assert_eq!(BigInt::from(0).to_i64(), Some(0));
+		assert_eq!(big("9223372036854775808").to_i64(), None);
+		assert_eq!(big("-9223372036854775808").to_i64(), Some(i64::MIN));
+		assert_eq!(big("-9223372036854775809").to_i64(), None);
+		assert_eq!(big("18446744073709551616").to_i64(), None);
+	}
+
+	#[test]
+	fn display() {
+		assert_eq!(BigInt::from(0).to_string(), "0");
+		assert_eq!(BigInt::from(-42).to_string(), "-42");
+		assert_eq!(BigInt::from(i64::MIN).to_string(), "-9223372036854775808");
+		// Chunks in the middle keep their leading zeros
+		assert_eq!(
+			big("1000000000000000000000000001").to_string(),
+			"1000000000000000000000000001"
+		);
+		assert_eq!(BigInt::parse("ff", 16).to_string(), "255");
+		assert_eq!(
+			BigInt::from(2).pow(100).unwrap().to_string(),
+			"1267650600228229401496703205376"
+		);
+		// Zero is never negative
+		assert_eq!((-&BigInt::from(0)).to_string(), "0");
+	}
+}
@@ -0,0 +1,408 @@
+use crate::bigint::BigInt;
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::ops::Neg;
+use std::str::FromStr;
+
+/// Digits after the decimal point a quotient gets if it does not end earlier
+const DIVISION_SCALE: u32 = 28;
+/// Keeps powers of ten needed to line up two scales well below the size limit of `BigInt`
+pub const MAX_SCALE: u32 = 1 << 16;
+
+/// Exact base 10 number with the value `value * 10^-scale`.
+///
+/// The scale is kept as written, so `1.10d` still displays both digits
+#[derive(Debug, Clone)]
+pub struct Decimal {
+	value: BigInt,
+	/// Number of digits after the decimal point
+	scale: u32,
+}
+
+/// How digits that do not fit are removed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+	/// Ties go to the even neighbour, also called banker's rounding
+	HalfEven,
+	/// Ties go away from zero
+	HalfUp,
+	/// Ties go towards zero
+	HalfDown,
+	/// Away from zero
+	Up,
+	/// Towards zero
+	Down,
+	/// Towards negative infinity
+	Floor,
+	/// Towards positive infinity
+	Ceiling,
+}
+
+impl FromStr for Rounding {
+	type Err = ();
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		Ok(match name {
+			"half_even" => Rounding::HalfEven,
+			"half_up" => Rounding::HalfUp,
+			"half_down" => Rounding::HalfDown,
+			"up" => Rounding::Up,
+			"down" => Rounding::Down,
+			"floor" => Rounding::Floor,
+			"ceiling" => Rounding::Ceiling,
+			_ => return Err(()),
+		})
+	}
+}
+
+impl Decimal {
+	/// `None` if the scale or the value is unreasonably large
+	fn new(value: BigInt, scale: u32) -> Option<Self> {
+		(scale <= MAX_SCALE && !value.is_too_large()).then_some(Self { value, scale })
+	}
+
+	/// Parses literals without separators like `12.50` or `2.5e-3`
+	pub fn parse(text: &str) -> Option<Self> {
+		let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+			Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+			None => (text, 0),
+		};
+		let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+		let value = BigInt::parse(&format!("{whole}{fraction}"), 10);
+
+		match (fraction.len() as i64).checked_sub(exponent)? {
+			scale if scale < 0 => {
+				let shift = u32::try_from(-scale)
+					.ok()
+					.filter(|shift| *shift <= MAX_SCALE)?;
+				Self::new(&value * &pow10(shift), 0)
+			}
+			scale => Self::new(value, u32::try_from(scale).ok()?),
+		}
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.value.is_zero()
+	}
+
+	/// Size of the value without the scale
+	pub fn bits(&self) -> u64 {
+		self.value.bits()
+	}
+
+	/// Value with trailing zeros of the fraction removed and the remaining scale,
+	/// equal decimals always produce the same pair
+	pub fn normalize(&self) -> (BigInt, u32) {
+		strip_zeros(self.value.clone(), self.scale, 0)
+	}
+
+	/// Expects `scale` to be at least the current scale
+	fn rescale(&self, scale: u32) -> BigInt {
+		&self.value * &pow10(scale - self.scale)
+	}
+
+	/// Both values at the larger of the two scales
+	fn align(&self, other: &Self) -> (BigInt, BigInt, u32) {
+		let scale = self.scale.max(other.scale);
+		(self.rescale(scale), other.rescale(scale), scale)
+	}
+
+	pub fn checked_add(&self, other: &Self) -> Option<Self> {
+		let (left, right, scale) = self.align(other);
+		Self::new(&left + &right, scale)
+	}
+
+	pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+		let (left, right, scale) = self.align(other);
+		Self::new(&left - &right, scale)
+	}
+
+	pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+		Self::new(
+			self.value.checked_mul(&other.value)?,
+			self.scale.checked_add(other.scale)?,
+		)
+	}
+
+	/// Exact if the quotient ends within `DIVISION_SCALE` digits, otherwise rounded half to even.
+	/// Trailing zeros are removed down to the difference of the scales, so `1.20d / 2d` is `0.60`
+	pub fn checked_div(&self, other: &Self) -> Option<Self> {
+		if other.is_zero() {
+			return None;
+		}
+		let ideal = self.scale.saturating_sub(other.scale);
+		let scale = self.scale.max(DIVISION_SCALE);
+		let numerator = &self.value * &pow10(scale + other.scale - self.scale);
+		let value = round_division(&numerator, &other.value, Rounding::HalfEven)?;
+		let (value, scale) = strip_zeros(value, scale, ideal);
+		Self::new(value, scale)
+	}
+
+	/// Rounds towards negative infinity and has no fraction
+	pub fn floor_div(&self, other: &Self) -> Option<Self> {
+		let (left, right, _) = self.align(other);
+		Self::new(left.floor_div(&right)?, 0)
+	}
+
+	/// Has the sign of `self`, like the remainder of integers
+	pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+		let (left, right, scale) = self.align(other);
+		Self::new(left.div_rem(&right)?.1, scale)
+	}
+
+	/// Negative exponents divide one by the power
+	pub fn checked_pow(&self, exponent: i64) -> Option<Self> {
+		if exponent < 0 {
+			let power = self.checked_pow(exponent.checked_neg()?)?;
+			return Self::from(BigInt::from(1)).checked_div(&power);
+		}
+		let exponent = u32::try_from(exponent).ok()?;
+		Self::new(self.value.pow(exponent)?, self.scale.checked_mul(exponent)?)
+	}
+
+	/// Result always has exactly `places` digits after the decimal point
+	pub fn round(&self, places: u32, mode: Rounding) -> Option<Self> {
+		if places > MAX_SCALE {
+			return None;
+		}
+		if places >= self.scale {
+			return Self::new(self.rescale(places), places);
+		}
+		let divisor = pow10(self.scale - places);
+		Self::new(round_division(&self.value, &divisor, mode)?, places)
+	}
+}
+
+impl From<BigInt> for Decimal {
+	fn from(value: BigInt) -> Self {
+		Self { value, scale: 0 }
+	}
+}
+
+impl Neg for &Decimal {
+	type Output = Decimal;
+
+	fn neg(self) -> Decimal {
+		Decimal {
+			value: -&self.value,
+			scale: self.scale,
+		}
+	}
+}
+
+/// Decimals are equal if they have the same value, `1.10d` and `1.1d` are equal
+impl PartialEq for Decimal {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Decimal {
+	fn cmp(&self, other: &Self) -> Ordering {
+		let (left, right, _) = self.align(other);
+		left.cmp(&right)
+	}
+}
+
+impl Display for Decimal {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if self.scale == 0 {
+			return write!(f, "{}", self.value);
+		}
+		let sign = if self.value.is_negative() { "-" } else { "" };
+		let digits = abs(&self.value).to_string();
+		// At least one digit in front of the decimal point
+		let digits = format!("{digits:0>width$}", width = self.scale as usize + 1);
+		let (whole, fraction) = digits.split_at(digits.len() - self.scale as usize);
+		write!(f, "{sign}{whole}.{fraction}")
+	}
+}
+
+/// Only used with exponents up to `MAX_SCALE + DIVISION_SCALE`, which always fit
+fn pow10(exponent: u32) -> BigInt {
+	BigInt::from(10)
+		.pow(exponent)
+		.expect("Powers of ten for valid scales are small enough")
+}
+
+/// Removes trailing zeros of the fraction as long as the scale stays at least `min_scale`
+fn strip_zeros(mut value: BigInt, mut scale: u32, min_scale: u32) -> (BigInt, u32) {
+	let ten = BigInt::from(10);
+	while scale > min_scale {
+		match value.div_rem(&ten) {
+			Some((quotient, remainder)) if remainder.is_zero() => value = quotient,
+			_ => break,
+		}
+		scale -= 1;
+	}
+	(value, scale)
+}
+
+fn abs(value: &BigInt) -> BigInt {
+	if value.is_negative() {
+		-value
+	} else {
+		value.clone()
+	}
+}
+
+/// Divides and removes the remainder according to `mode`, `None` when dividing by zero
+fn round_division(numerator: &BigInt, denominator: &BigInt, mode: Rounding) -> Option<BigInt> {
+	let (quotient, remainder) = numerator.div_rem(denominator)?;
+	if remainder.is_zero() {
+		return Some(quotient);
+	}
+
+	let negative = numerator.is_negative() != denominator.is_negative();
+	let away_from_zero = match mode {
+		Rounding::Up => true,
+		Rounding::Down => false,
+		Rounding::Floor => negative,
+		Rounding::Ceiling => !negative,
+		Rounding::HalfEven | Rounding::HalfUp | Rounding::HalfDown => {
+			let twice = abs(&(&remainder + &remainder));
+			match twice.cmp(&abs(denominator)) {
+				Ordering::Greater => true,
+				Ordering::Less => false,
+				Ordering::Equal => match mode {
+					Rounding::HalfUp => true,
+					Rounding::HalfDown => false,
+					_ => !quotient.div_rem(&BigInt::from(2))?.1.is_zero(),
+				},
+			}
+		}
+	};
+
+	Some(match (away_from_zero, negative) {
+		(false, _) => quotient,
+		(true, false) => &quotient + &BigInt::from(1),
+		(true, true) => &quotient - &BigInt::from(1),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn dec(text: &str) -> Decimal {
+		match text.strip_prefix('-') {
+			Some(text) => -&Decimal::parse(text).unwrap(),
+			None => Decimal::parse(text).unwrap(),
+		}
+	}
+
+	fn div(left: &str, right: &str) -> String {
+		dec(left).checked_div(&dec(right)).unwrap().to_string()
+	}
+
+	/// Rounds 2.5, 3.5, -2.5, -3.5, 2.4, 2.6, -2.4 and -2.6 to whole numbers
+	fn round_all(mode: Rounding) -> Vec<String> {
+		["2.5", "3.5", "-2.5", "-3.5", "2.4", "2.6", "-2.4", "-2.6"]
+			.iter()
+			.map(|value| dec(value).round(0, mode).unwrap().to_string())
+			.collect()
+	}
+
+	#[test]
+	fn round_modes() {
+		let cases = [
+			(
+				Rounding::HalfEven,
+				["2", "4", "-2", "-4", "2", "3", "-2", "-3"],
+			),
+			(
+				Rounding::HalfUp,
+				["3", "4", "-3", "-4", "2", "3", "-2", "-3"],
+			),
+			(
+				Rounding::HalfDown,
+				["2", "3", "-2", "-3", "2", "3", "-2", "-3"],
+			),
+			(Rounding::Up, ["3", "4", "-3", "-4", "3", "3", "-3", "-3"]),
+			(Rounding::Down, ["2", "3", "-2", "-3", "2", "2", "-2", "-2"]),
+			(
+				Rounding::Floor,
+				["2", "3", "-3", "-4", "2", "2", "-3", "-3"],
+			),
+			(
+				Rounding::Ceiling,
+				["3", "4", "-2", "-3", "3", "3", "-2", "-2"],
+			),
+		];
+		for (mode, expected) in cases {
+			assert_eq!(round_all(mode), expected, "{mode:?}");
+		}
+	}
+
+	#[test]
+	fn round_places() {
+		let round = |value: &str, places, mode| dec(value).round(places, mode).unwrap().to_string();
+		assert_eq!(round("1.005", 2, Rounding::HalfEven), "1.00");
+		assert_eq!(round("1.015", 2, Rounding::HalfEven), "1.02");
+		assert_eq!(round("1.005", 2, Rounding::HalfUp), "1.01");
+		assert_eq!(round("1.5", 3, Rounding::Down), "1.500");
+		assert_eq!(round("2.00", 0, Rounding::Up), "2");
+		// Zero has no sign
+		assert_eq!(round("-0.4", 0, Rounding::HalfEven), "0");
+		assert_eq!(round("-0.4", 0, Rounding::Down), "0");
+		assert!(dec("1").round(MAX_SCALE + 1, Rounding::Down).is_none());
+	}
+
+	#[test]
+	fn div_exact() {
+		assert_eq!(div("1", "4"), "0.25");
+		assert_eq!(div("1", "8"), "0.125");
+		assert_eq!(div("1.20", "2"), "0.60");
+		assert_eq!(div("10.00", "4"), "2.50");
+		assert_eq!(div("6", "3"), "2");
+		assert_eq!(div("-1", "4"), "-0.25");
+		assert!(dec("1").checked_div(&dec("0.00")).is_none());
+	}
+
+	#[test]
+	fn div_rounds_at_scale_limit() {
+		assert_eq!(div("1", "3"), "0.3333333333333333333333333333");
+		assert_eq!(div("2", "3"), "0.6666666666666666666666666667");
+		assert_eq!(div("-2", "3"), "-0.6666666666666666666666666667");
+		// Ties at the last place go to the even neighbour
+		assert_eq!(div("1", "2e28"), "0");
+		assert_eq!(div("3", "2e28"), "0.0000000000000000000000000002");
+		// More places than the limit are kept
+		assert_eq!(
+			div("1.00000000000000000000000000000001", "1"),
+			"1.00000000000000000000000000000001"
+		);
+	}
+
+	#[test]
+	fn normalize_keys() {
+		let key = |value: &str| {
+			let (value, scale) = dec(value).normalize();
+			(value.to_string(), scale)
+		};
+		assert_eq!(key("1.10"), ("11".into(), 1));
+		assert_eq!(key("1.1"), key("1.10"));
+		assert_eq!(key("2.000"), ("2".into(), 0));
+		assert_eq!(key("100"), ("100".into(), 0));
+		assert_eq!(key("0.00"), ("0".into(), 0));
+		assert_eq!(key("-1.50"), ("-15".into(), 1));
+		assert_eq!(key("2.5e2"), ("250".into(), 0));
+	}
+
+	#[test]
+	fn pow() {
+		let pow = |value: &str, exponent| dec(value).checked_pow(exponent).unwrap().to_string();
+		assert_eq!(pow("1.5", 2), "2.25");
+		assert_eq!(pow("2", -2), "0.25");
+		assert_eq!(pow("0.1", 0), "1");
+		assert!(dec("0").checked_pow(-1).is_none());
+	}
+}
@@ -1,4 +1,6 @@
 use crate::literal::Literal;
+use std::fmt::Display;
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub enum Stmt {
@@ -9,24 +11,184 @@ pub enum Stmt {
 		initializer: Expr,
 		mutable: bool,
 	},
-	Block(Vec<Stmt>),
+	/// `const (a, _) = pair;`, `None` skips a value
+	Destructure {
+		names: Vec<Option<String>>,
+		initializer: Expr,
+		mutable: bool,
+	},
+	Fun(Rc<FunDecl>),
+	Struct {
+		name: String,
+		parent: Option<String>,
+		fields: Vec<String>,
+	},
+	Enum {
+		name: String,
+		variants: Vec<VariantDecl>,
+	},
+	/// Methods for the struct called `name`
+	Impl {
+		name: String,
+		methods: Vec<Rc<FunDecl>>,
+	},
+}
+
+#[derive(Debug)]
+pub struct VariantDecl {
+	pub name: String,
+	pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct FunDecl {
+	pub name: String,
+	pub params: Vec<String>,
+	pub body: Block,
 }
 
 #[derive(Debug)]
 pub enum Expr {
 	Binary(Box<Expr>, BinaryOp, Box<Expr>),
+	Logical(Box<Expr>, LogicalOp, Box<Expr>),
 	Unary(UnaryOp, Box<Expr>),
 	Literal(Literal),
+	/// `"a {b} c"`, the parts are joined into one string
+	Interpolation(Vec<Expr>),
 	Group(Box<Expr>),
 	// TODO: Should this really be an expression or should assignment be a statement?
-	Assign(String, Box<Expr>),
+	Assign(AssignTarget, Box<Expr>),
+	/// `target op= value`, like `x += 1`
+	CompoundAssign(AssignTarget, BinaryOp, Box<Expr>),
+	Var(String),
+	/// `Enum::Variant`
+	Path(String, String),
+	Call(Box<Expr>, Vec<Expr>),
+	Tuple(Vec<Expr>),
+	List(Vec<Expr>),
+	/// `#{ key: value }`, keys are evaluated in order
+	Map(Vec<(Expr, Expr)>),
+	/// `object[index]`, the index can also be a range
+	Index(Box<Expr>, Box<Expr>),
+	/// Field or method access `object.name`
+	Get(Box<Expr>, String),
+	This,
+	/// Method of the parent struct `super.name`
+	Super(String),
+	StructLiteral {
+		name: String,
+		fields: Vec<(String, Expr)>,
+	},
+	Block(Block),
+	If {
+		condition: Box<Expr>,
+		then_branch: Block,
+		/// Either another `Expr::If` or an `Expr::Block`
+		else_branch: Option<Box<Expr>>,
+	},
+	While {
+		condition: Box<Expr>,
+		body: Block,
+	},
+	Loop(Block),
+	For {
+		name: String,
+		iterable: Box<Expr>,
+		body: Block,
+	},
+	Match {
+		value: Box<Expr>,
+		arms: Vec<MatchArm>,
+	},
+	Range {
+		start: Box<Expr>,
+		end: Box<Expr>,
+		step: Option<Box<Expr>>,
+		inclusive: bool,
+	},
+	Break(Option<Box<Expr>>),
+	Continue,
+	Return(Option<Box<Expr>>),
+}
+
+#[derive(Debug)]
+pub enum AssignTarget {
 	Var(String),
+	Field(Box<Expr>, String),
+	Index(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+pub struct MatchArm {
+	pub pattern: Pattern,
+	pub guard: Option<Expr>,
+	pub body: Expr,
+}
+
+#[derive(Debug)]
+pub enum Pattern {
+	/// Compared using the same equality as `==`
+	Literal(Literal),
+	Range {
+		start: Literal,
+		end: Literal,
+		inclusive: bool,
+	},
+	Or(Vec<Pattern>),
+	/// `Enum::Variant` optionally followed by patterns for its values
+	Variant {
+		path: (String, String),
+		values: Vec<Pattern>,
+	},
+	/// Matches anything and binds it to the name
+	Binding(String),
+	/// `_`, matches anything
+	Wildcard,
+}
+
+impl Pattern {
+	/// Whether the pattern matches every possible value
+	pub fn is_catch_all(&self) -> bool {
+		match self {
+			Pattern::Binding(_) | Pattern::Wildcard => true,
+			Pattern::Or(patterns) => patterns.iter().any(Pattern::is_catch_all),
+			Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Variant { .. } => false,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Block {
+	pub stmts: Vec<Stmt>,
+	/// Trailing expression without a `;`, its value is the value of the whole block
+	pub value: Option<Box<Expr>>,
 }
 
 #[derive(Debug)]
 pub enum UnaryOp {
 	Neg,
 	Not,
+	BitNot,
+}
+
+impl Display for UnaryOp {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{}",
+			match self {
+				UnaryOp::Neg => "-",
+				UnaryOp::Not => "!",
+				UnaryOp::BitNot => "~",
+			}
+		)
+	}
+}
+
+#[derive(Debug)]
+pub enum LogicalOp {
+	And,
+	Or,
 }
 
 // TODO: Maybe add power
@@ -41,5 +203,46 @@ pub enum BinaryOp {
 	Add,
 	Sub,
 	Mul,
+	/// Always produces a float
 	Div,
+	/// `~/`, rounds towards negative infinity
+	FloorDiv,
+	/// Takes the sign of the left side, like `%` in Rust
+	Rem,
+	Pow,
+	BitAnd,
+	BitOr,
+	BitXor,
+	Shl,
+	/// Arithmetic shift, keeps the sign
+	Shr,
+}
+
+impl Display for BinaryOp {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{}",
+			match self {
+				BinaryOp::Equal => "==",
+				BinaryOp::NotEqual => "!=",
+				BinaryOp::Greater => ">",
+				BinaryOp::GreaterEqual => ">=",
+				BinaryOp::Less => "<",
+				BinaryOp::LessEqual => "<=",
+				BinaryOp::Add => "+",
+				BinaryOp::Sub => "-",
+				BinaryOp::Mul => "*",
+				BinaryOp::Div => "/",
+				BinaryOp::FloorDiv => "~/",
+				BinaryOp::Rem => "%",
+				BinaryOp::Pow => "**",
+				BinaryOp::BitAnd => "&",
+				BinaryOp::BitOr => "|",
+				BinaryOp::BitXor => "^",
+				BinaryOp::Shl => "<<",
+				BinaryOp::Shr => ">>",
+			}
+		)
+	}
 }
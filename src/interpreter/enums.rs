@@ -0,0 +1,84 @@
+use super::error::RuntimeError;
+use crate::literal::Literal;
+use std::fmt::{Debug, Display};
+use std::rc::Rc;
+
+/// Type created by an `enum` declaration
+pub struct Enum {
+	pub name: String,
+	/// Name and number of values of every variant
+	pub variants: Vec<(String, usize)>,
+}
+
+impl Enum {
+	pub fn find_variant(&self, name: &str) -> Result<usize, RuntimeError> {
+		match self
+			.variants
+			.iter()
+			.position(|(variant, _)| variant == name)
+		{
+			Some(index) => Ok(index),
+			None => RuntimeError::unknown_variant(&self.name, name),
+		}
+	}
+
+	pub fn construct(
+		self: &Rc<Self>,
+		index: usize,
+		values: Vec<Literal>,
+	) -> Result<Variant, RuntimeError> {
+		let (name, arity) = &self.variants[index];
+		if values.len() != *arity {
+			return RuntimeError::arity(&format!("{}::{name}", self.name), *arity, values.len());
+		}
+
+		Ok(Variant {
+			class: self.clone(),
+			index,
+			values,
+		})
+	}
+}
+
+impl PartialEq for Enum {
+	fn eq(&self, other: &Self) -> bool {
+		std::ptr::eq(self, other)
+	}
+}
+
+impl Debug for Enum {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "<enum {}>", self.name)
+	}
+}
+
+/// Value of an enum, compared structurally by `==`
+#[derive(Debug, PartialEq)]
+pub struct Variant {
+	pub class: Rc<Enum>,
+	index: usize,
+	pub values: Vec<Literal>,
+}
+
+impl Variant {
+	pub fn name(&self) -> &str {
+		&self.class.variants[self.index].0
+	}
+}
+
+impl Display for Variant {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}::{}", self.class.name, self.name())?;
+		if self.values.is_empty() {
+			return Ok(());
+		}
+		write!(f, "(")?;
+		for (i, value) in self.values.iter().enumerate() {
+			if i > 0 {
+				write!(f, ", ")?;
+			}
+			write!(f, "{value}")?;
+		}
+		write!(f, ")")
+	}
+}
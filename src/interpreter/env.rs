@@ -1,6 +1,8 @@
 use super::error::RuntimeError;
 use crate::literal::Literal;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 pub struct Variable {
 	value: Literal,
@@ -9,71 +11,81 @@ pub struct Variable {
 
 type Scope = HashMap<String, Variable>;
 
-pub struct Env {
-	scopes: Vec<Scope>,
+struct Frame {
+	scope: Scope,
+	parent: Option<Env>,
 }
 
+/// Handle to a chain of scopes.
+///
+/// Cloning it shares the underlying frames, which is how closures
+/// keep the variables they capture alive after their block has ended.
+///
+/// A function declared in a frame is stored in that same frame while also holding it as its closure.
+/// This reference cycle is never collected, so the frames of functions that are declared
+/// inside of other functions leak until the program exits.
+/// A `Weak` closure would break returned closures, whose frame has no other owner.
+#[derive(Clone)]
+pub struct Env(Rc<RefCell<Frame>>);
+
 impl Env {
 	pub fn new() -> Self {
-		Self {
-			scopes: vec![Scope::new()],
-		}
+		Self(Rc::new(RefCell::new(Frame {
+			scope: Scope::new(),
+			parent: None,
+		})))
 	}
 
-	pub fn push_scope(&mut self) {
-		self.scopes.push(Scope::new())
+	/// Creates a new scope that has `self` as its parent
+	pub fn child(&self) -> Self {
+		Self(Rc::new(RefCell::new(Frame {
+			scope: Scope::new(),
+			parent: Some(self.clone()),
+		})))
 	}
 
-	pub fn pop_scope(&mut self) {
-		if self.scopes.len() > 1 {
-			self.scopes.pop();
+	pub fn get(&self, name: &str) -> Result<Literal, RuntimeError> {
+		let frame = self.0.borrow();
+		match (frame.scope.get(name), &frame.parent) {
+			(Some(var), _) => Ok(var.value.clone()),
+			(None, Some(parent)) => parent.get(name),
+			(None, None) => RuntimeError::undeclared_var(name),
 		}
 	}
 
-	pub fn get(&self, name: &str) -> Result<&Literal, RuntimeError> {
-		for scope in self.iter() {
-			match scope.get(name) {
-				Some(var) => return Ok(&var.value),
-				None => continue,
+	pub fn set(&self, name: &str, new: Literal) -> Result<(), RuntimeError> {
+		let mut frame = self.0.borrow_mut();
+		if let Some(var) = frame.scope.get_mut(name) {
+			if !var.mutable {
+				return RuntimeError::assignment(name, new);
 			}
+			var.value = new;
+			return Ok(());
 		}
-		RuntimeError::undeclared_var(name)
-	}
-
-	pub fn set(&mut self, name: &str, new: Literal) -> Result<(), RuntimeError> {
-		for scope in self.iter_mut() {
-			match scope.get_mut(name) {
-				Some(var) => {
-					if !var.mutable {
-						return RuntimeError::assignment(name, new);
-					}
-					var.value = new;
-					return Ok(());
-				}
-				None => continue,
-			}
+		match &frame.parent {
+			Some(parent) => parent.set(name, new),
+			None => RuntimeError::undeclared_var(name),
 		}
-		RuntimeError::undeclared_var(name)
 	}
 
-	pub fn declare(&mut self, name: String, value: Literal, mutable: bool) {
-		self.last_mut().insert(name, Variable { value, mutable });
+	pub fn is_mutable(&self, name: &str) -> Result<bool, RuntimeError> {
+		let frame = self.0.borrow();
+		match (frame.scope.get(name), &frame.parent) {
+			(Some(var), _) => Ok(var.mutable),
+			(None, Some(parent)) => parent.is_mutable(name),
+			(None, None) => RuntimeError::undeclared_var(name),
+		}
 	}
-}
 
-impl Env {
-	fn last_mut(&mut self) -> &mut Scope {
+	pub fn declare(&self, name: String, value: Literal, mutable: bool) {
 		self
-			.scopes
-			.last_mut()
-			.expect("Internal Error: Stack should never be empty!")
-	}
-
-	fn iter(&self) -> std::iter::Rev<std::slice::Iter<Scope>> {
-		self.scopes.iter().rev()
+			.0
+			.borrow_mut()
+			.scope
+			.insert(name, Variable { value, mutable });
 	}
 
-	fn iter_mut(&mut self) -> std::iter::Rev<std::slice::IterMut<Scope>> {
-		self.scopes.iter_mut().rev()
+	pub fn ptr_eq(&self, other: &Self) -> bool {
+		Rc::ptr_eq(&self.0, &other.0)
 	}
 }
@@ -1,4 +1,8 @@
-use crate::{expr::UnaryOp, literal::Literal};
+use crate::bigint::{BigInt, MAX_BITS};
+use crate::decimal::MAX_SCALE;
+use crate::expr::{BinaryOp, LogicalOp, UnaryOp};
+use crate::literal::Literal;
+use std::fmt::Display;
 
 #[derive(Debug)]
 pub enum RuntimeError {
@@ -6,6 +10,17 @@ pub enum RuntimeError {
 	TypeMismatch(String),
 	UndeclaredVar(String),
 	Assignment(String),
+	Condition(String),
+	Arity(String),
+	StructField(String),
+	UnknownMember(String),
+	NoMatch(String),
+	Overflow(String),
+	TooLarge(String),
+	NotIntegral(String),
+	IndexOutOfBounds(String),
+	MissingKey(String),
+	Recursion(String),
 	DivideByZero,
 }
 
@@ -17,20 +32,264 @@ impl RuntimeError {
 		)))
 	}
 
+	pub fn compound_assignment<T>(name: &str, op: &BinaryOp) -> Result<T, Self> {
+		Err(Self::Assignment(format!(
+			"Cannot assign to constant {name} with `{op}=`"
+		)))
+	}
+
+	/// `member` describes what is assigned to, like "field `x`"
+	pub fn member_assignment<T>(name: &str, member: &str, op: Option<&BinaryOp>) -> Result<T, Self> {
+		Err(Self::Assignment(format!(
+			"Cannot assign to {member} of constant {name}{}",
+			match op {
+				Some(op) => format!(" with `{op}=`"),
+				None => std::string::String::new(),
+			}
+		)))
+	}
+
 	pub fn undeclared_var<T>(name: &str) -> Result<T, Self> {
 		Err(Self::UndeclaredVar(format!(
 			"Variable `{name}` has not been declared"
 		)))
 	}
 
-	pub fn unary<T>(op: UnaryOp, right: Literal) -> Result<T, Self> {
+	pub fn condition<T>(value: Literal) -> Result<T, Self> {
+		Err(Self::Condition(format!(
+			"Condition has to be `true` or `false`, got `{}`",
+			value.to_type_string()
+		)))
+	}
+
+	pub fn range<T>(start: Literal, end: Literal) -> Result<T, Self> {
+		Err(Self::TypeMismatch(format!(
+			"Cannot create a range from `{}` to `{}`",
+			start.to_type_string(),
+			end.to_type_string(),
+		)))
+	}
+
+	pub fn range_step<T>(step: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Range step has to be an `int` other than zero, got `{step}`"
+		)))
+	}
+
+	pub fn iteration<T>(value: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot iterate over `{}`",
+			value.to_type_string()
+		)))
+	}
+
+	pub fn index<T>(object: Literal, index: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot index `{}` with `{}`",
+			object.to_type_string(),
+			index.to_type_string(),
+		)))
+	}
+
+	pub fn index_out_of_bounds<T>(index: i64, len: usize) -> Result<T, Self> {
+		Err(Self::IndexOutOfBounds(format!(
+			"Index {index} is out of bounds, the length is {len}"
+		)))
+	}
+
+	pub fn missing_key<T>(key: Literal) -> Result<T, Self> {
+		Err(Self::MissingKey(format!("Map has no key `{key}`")))
+	}
+
+	pub fn map_key<T>(key: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot use `{}` as a map key, only strings, chars, numbers and booleans are allowed",
+			key.to_type_string()
+		)))
+	}
+
+	pub fn nan_key<T>() -> Result<T, Self> {
+		Err(Self::ForbiddenType(
+			"Cannot use `NaN` as a map key, it is not equal to itself".into(),
+		))
+	}
+
+	pub fn destructure<T>(value: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot destructure `{}`, only tuples can be destructured",
+			value.to_type_string()
+		)))
+	}
+
+	pub fn destructure_arity<T>(expected: usize, got: usize) -> Result<T, Self> {
+		Err(Self::Arity(format!(
+			"Cannot destructure a tuple of {got} value(s) into {expected} name(s)"
+		)))
+	}
+
+	pub fn call<T>(callee: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot call `{}`, only functions are callable",
+			callee.to_type_string()
+		)))
+	}
+
+	pub fn arity<T>(name: &str, expected: usize, got: usize) -> Result<T, Self> {
+		Err(Self::Arity(format!(
+			"Function `{name}` expects {expected} argument(s), got {got}"
+		)))
+	}
+
+	pub fn not_a_struct<T>(name: &str, value: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot instantiate `{name}` of type `{}`, it is not a struct",
+			value.to_type_string()
+		)))
+	}
+
+	pub fn field_access<T>(object: Literal, field: &str) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot access field `{field}` of `{}`",
+			object.to_type_string()
+		)))
+	}
+
+	pub fn unknown_field<T>(class: &str, field: &str) -> Result<T, Self> {
+		Err(Self::StructField(format!(
+			"Struct `{class}` has no field `{field}`"
+		)))
+	}
+
+	pub fn unknown_member<T>(class: &str, name: &str) -> Result<T, Self> {
+		Err(Self::UnknownMember(format!(
+			"Struct `{class}` has no field or method `{name}`"
+		)))
+	}
+
+	pub fn unknown_variant<T>(class: &str, name: &str) -> Result<T, Self> {
+		Err(Self::UnknownMember(format!(
+			"Enum `{class}` has no variant `{name}`"
+		)))
+	}
+
+	pub fn path<T>(value: Literal, name: &str) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot access `{name}` of `{}` with `::`, only enums have variants",
+			value.to_type_string()
+		)))
+	}
+
+	pub fn unknown_method<T>(class: &str, name: &str) -> Result<T, Self> {
+		Err(Self::UnknownMember(format!(
+			"Struct `{class}` has no method `{name}`"
+		)))
+	}
+
+	pub fn no_parent<T>() -> Result<T, Self> {
+		Err(Self::UnknownMember(
+			"Cannot use `super`, the struct has no parent".into(),
+		))
+	}
+
+	pub fn missing_field<T>(class: &str, field: &str) -> Result<T, Self> {
+		Err(Self::StructField(format!(
+			"Missing field `{field}` in literal of struct `{class}`"
+		)))
+	}
+
+	pub fn duplicate_field<T>(class: &str, field: &str) -> Result<T, Self> {
+		Err(Self::StructField(format!(
+			"Field `{field}` of struct `{class}` is specified more than once"
+		)))
+	}
+
+	pub fn redeclared_field<T>(class: &str, field: &str) -> Result<T, Self> {
+		Err(Self::StructField(format!(
+			"Field `{field}` is declared more than once in struct `{class}` and its parents"
+		)))
+	}
+
+	pub fn recursion<T>(limit: usize) -> Result<T, Self> {
+		Err(Self::Recursion(format!(
+			"Maximum recursion depth of {limit} calls exceeded"
+		)))
+	}
+
+	pub fn no_match<T>(value: Literal) -> Result<T, Self> {
+		Err(Self::NoMatch(format!("No match arm matches `{value}`")))
+	}
+
+	pub fn remainder<T>(left: Literal, right: Literal) -> Result<T, Self> {
+		Err(Self::TypeMismatch(format!(
+			"Cannot take the remainder of `{}` divided by `{}`",
+			left.to_type_string(),
+			right.to_type_string(),
+		)))
+	}
+
+	pub fn exponentiation<T>(left: Literal, right: Literal) -> Result<T, Self> {
+		Err(Self::TypeMismatch(format!(
+			"Cannot raise `{}` to the power of `{}`",
+			left.to_type_string(),
+			right.to_type_string(),
+		)))
+	}
+
+	pub fn bitwise<T>(op: &BinaryOp, left: Literal, right: Literal) -> Result<T, Self> {
+		Err(Self::TypeMismatch(format!(
+			"Cannot apply bitwise operator `{op}` to `{}` and `{}`",
+			left.to_type_string(),
+			right.to_type_string(),
+		)))
+	}
+
+	pub fn not_integral<T>(op: &dyn Display, value: f64) -> Result<T, Self> {
+		Err(Self::NotIntegral(format!(
+			"Operator `{op}` only works on integers, `{value}` is not one"
+		)))
+	}
+
+	pub fn bitwise_range<T>(op: &dyn Display, value: &BigInt) -> Result<T, Self> {
+		Err(Self::Overflow(format!(
+			"Operator `{op}` only works on integers that fit into 64 bits, `{value}` does not"
+		)))
+	}
+
+	pub fn shift<T>(amount: i64) -> Result<T, Self> {
+		Err(Self::Overflow(format!(
+			"Cannot shift by {amount}, the amount has to be between 0 and 63"
+		)))
+	}
+
+	pub fn too_large<T>(left: &Literal, op: &BinaryOp, right: &Literal) -> Result<T, Self> {
+		// Printing huge numbers takes long and does not help anyone
+		let describe = |value: &Literal| match value {
+			Literal::BigInt(value) if value.bits() > 128 => format!("<{} bit int>", value.bits()),
+			Literal::Decimal(value) if value.bits() > 128 => format!("<{} bit decimal>", value.bits()),
+			value => value.to_string(),
+		};
+		Err(Self::TooLarge(format!(
+			"Result of `{} {op} {}` is too large, numbers are limited to {MAX_BITS} bits and {MAX_SCALE} decimal places",
+			describe(left),
+			describe(right),
+		)))
+	}
+
+	pub fn unary<T>(op: &UnaryOp, right: Literal) -> Result<T, Self> {
 		Err(Self::ForbiddenType(format!(
-			"Cannot apply unary operator `{}` to `{}`",
+			"Cannot apply unary operator `{op}` to `{}`",
+			right.to_type_string(),
+		)))
+	}
+
+	pub fn logical<T>(op: &LogicalOp, value: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot apply logical operator `{}` to `{}`",
 			match op {
-				UnaryOp::Neg => "-",
-				UnaryOp::Not => "!",
+				LogicalOp::And => "and",
+				LogicalOp::Or => "or",
 			},
-			right.to_type_string(),
+			value.to_type_string(),
 		)))
 	}
 
@@ -42,6 +301,31 @@ impl RuntimeError {
 		)))
 	}
 
+	pub fn unordered<T>(left: Literal, right: Literal) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot order `{left}` and `{right}`, they are neither smaller, equal nor greater"
+		)))
+	}
+
+	pub fn argument<T>(name: &str, expected: &str, got: Literal) -> Result<T, Self> {
+		Err(Self::TypeMismatch(format!(
+			"Function `{name}` expects `{expected}` arguments, got `{}`",
+			got.to_type_string()
+		)))
+	}
+
+	pub fn rounding<T>(mode: &str) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Unknown rounding mode `{mode}`, expected one of `half_even`, `half_up`, `half_down`, `up`, `down`, `floor` or `ceiling`"
+		)))
+	}
+
+	pub fn places<T>(places: i64) -> Result<T, Self> {
+		Err(Self::ForbiddenType(format!(
+			"Cannot round to {places} places, the number has to be between 0 and {MAX_SCALE}"
+		)))
+	}
+
 	pub fn addition<T>(left: Literal, right: Literal) -> Result<T, Self> {
 		Err(Self::TypeMismatch(format!(
 			"Cannot add `{}` to `{}`",
@@ -74,3 +358,26 @@ impl RuntimeError {
 		)))
 	}
 }
+
+impl Display for RuntimeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			RuntimeError::ForbiddenType(msg)
+			| RuntimeError::TypeMismatch(msg)
+			| RuntimeError::UndeclaredVar(msg)
+			| RuntimeError::Assignment(msg)
+			| RuntimeError::Condition(msg)
+			| RuntimeError::Arity(msg)
+			| RuntimeError::StructField(msg)
+			| RuntimeError::UnknownMember(msg)
+			| RuntimeError::NoMatch(msg)
+			| RuntimeError::Overflow(msg)
+			| RuntimeError::TooLarge(msg)
+			| RuntimeError::NotIntegral(msg)
+			| RuntimeError::IndexOutOfBounds(msg)
+			| RuntimeError::MissingKey(msg)
+			| RuntimeError::Recursion(msg) => write!(f, "{msg}"),
+			RuntimeError::DivideByZero => write!(f, "Cannot divide by zero"),
+		}
+	}
+}
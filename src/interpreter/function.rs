@@ -0,0 +1,40 @@
+use super::env::Env;
+use crate::expr::FunDecl;
+use crate::literal::Literal;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+pub struct Function {
+	pub decl: Rc<FunDecl>,
+	/// Scope the function was declared in, which usually also holds the function itself,
+	/// see `Env` for the resulting reference cycle
+	pub closure: Env,
+}
+
+impl Function {
+	pub fn arity(&self) -> usize {
+		self.decl.params.len()
+	}
+
+	/// Turns a method into a function that has access to `this`
+	pub fn bind(&self, this: Literal) -> Self {
+		let closure = self.closure.child();
+		closure.declare("this".into(), this, false);
+		Self {
+			decl: self.decl.clone(),
+			closure,
+		}
+	}
+}
+
+impl PartialEq for Function {
+	fn eq(&self, other: &Self) -> bool {
+		Rc::ptr_eq(&self.decl, &other.decl) && self.closure.ptr_eq(&other.closure)
+	}
+}
+
+impl Debug for Function {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "<fun {}>", self.decl.name)
+	}
+}
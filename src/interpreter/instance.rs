@@ -0,0 +1,100 @@
+use super::error::RuntimeError;
+use super::function::Function;
+use crate::literal::Literal;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::rc::Rc;
+
+/// Type created by a `struct` declaration
+pub struct Struct {
+	pub name: String,
+	pub parent: Option<Rc<Struct>>,
+	/// Includes the fields of all parents
+	pub fields: Vec<String>,
+	/// Added to by `impl` blocks
+	pub methods: RefCell<HashMap<String, Rc<Function>>>,
+}
+
+impl Struct {
+	/// Searches the struct itself first and then its parents
+	pub fn find_method(&self, name: &str) -> Option<Rc<Function>> {
+		match self.methods.borrow().get(name) {
+			Some(method) => Some(method.clone()),
+			None => self.parent.as_ref()?.find_method(name),
+		}
+	}
+
+	/// Checks that `values` contains every field exactly once
+	pub fn instantiate(
+		self: &Rc<Self>,
+		values: Vec<(String, Literal)>,
+	) -> Result<Rc<RefCell<Instance>>, RuntimeError> {
+		let mut fields = HashMap::new();
+		for (name, value) in values {
+			if !self.fields.contains(&name) {
+				return RuntimeError::unknown_field(&self.name, &name);
+			}
+			if fields.insert(name.clone(), value).is_some() {
+				return RuntimeError::duplicate_field(&self.name, &name);
+			}
+		}
+		if let Some(missing) = self.fields.iter().find(|name| !fields.contains_key(*name)) {
+			return RuntimeError::missing_field(&self.name, missing);
+		}
+
+		Ok(Rc::new(RefCell::new(Instance {
+			class: self.clone(),
+			fields,
+		})))
+	}
+}
+
+impl PartialEq for Struct {
+	fn eq(&self, other: &Self) -> bool {
+		std::ptr::eq(self, other)
+	}
+}
+
+impl Debug for Struct {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "<struct {}>", self.name)
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Instance {
+	pub class: Rc<Struct>,
+	fields: HashMap<String, Literal>,
+}
+
+impl Instance {
+	pub fn get(&self, name: &str) -> Option<Literal> {
+		self.fields.get(name).cloned()
+	}
+
+	pub fn set(&mut self, name: &str, value: Literal) -> Result<(), RuntimeError> {
+		match self.fields.get_mut(name) {
+			Some(field) => {
+				*field = value;
+				Ok(())
+			}
+			None => RuntimeError::unknown_field(&self.class.name, name),
+		}
+	}
+}
+
+impl Display for Instance {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {{", self.class.name)?;
+		for (i, name) in self.class.fields.iter().enumerate() {
+			let separator = if i == 0 { " " } else { ", " };
+			write!(f, "{separator}{name}: {}", self.fields[name])?;
+		}
+		if self.class.fields.is_empty() {
+			write!(f, "}}")
+		} else {
+			write!(f, " }}")
+		}
+	}
+}
@@ -0,0 +1,18 @@
+use super::error::RuntimeError;
+use crate::literal::Literal;
+
+/// Stops the normal evaluation of statements and unwinds
+/// until it reaches the construct that handles it.
+#[derive(Debug)]
+pub enum Interrupt {
+	Break(Literal),
+	Continue,
+	Return(Literal),
+	Error(RuntimeError),
+}
+
+impl From<RuntimeError> for Interrupt {
+	fn from(err: RuntimeError) -> Self {
+		Self::Error(err)
+	}
+}
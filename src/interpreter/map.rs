@@ -0,0 +1,119 @@
+use super::error::RuntimeError;
+use crate::bigint::BigInt;
+use crate::literal::Literal;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// Hashable form of the values that can be used as map keys.
+///
+/// Whole floats become integers, so `1` and `1.0` are the same key just like they are
+/// equal with `==`. `NaN` is rejected because it is not equal to itself and could
+/// never be looked up again.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Key {
+	True,
+	False,
+	Int(i64),
+	BigInt(BigInt),
+	/// Value and scale of a decimal with a fraction, without trailing zeros
+	Decimal(BigInt, u32),
+	/// Bits of a float that has a fractional part or is infinite
+	Float(u64),
+	/// Chars are stored as strings, because `'a'` is equal to `"a"`
+	String(String),
+}
+
+impl Key {
+	fn new(literal: &Literal) -> Result<Self, RuntimeError> {
+		Ok(match literal {
+			Literal::True => Key::True,
+			Literal::False => Key::False,
+			Literal::Int(value) => Key::Int(*value),
+			Literal::BigInt(value) => Key::BigInt((**value).clone()),
+			// Whole decimals are the same key as the integer they are equal to
+			Literal::Decimal(value) => match value.normalize() {
+				(value, 0) => match value.to_i64() {
+					Some(value) => Key::Int(value),
+					None => Key::BigInt(value),
+				},
+				(value, scale) => Key::Decimal(value, scale),
+			},
+			Literal::Number(value) if value.is_nan() => return RuntimeError::nan_key(),
+			Literal::Number(value)
+				if value.fract() == 0.0 && (i64::MIN as f64..-(i64::MIN as f64)).contains(value) =>
+			{
+				Key::Int(*value as i64)
+			}
+			Literal::Number(value) => match BigInt::from_f64(*value) {
+				Some(value) => Key::BigInt(value),
+				None => Key::Float(value.to_bits()),
+			},
+			Literal::Char(value) => Key::String(value.to_string()),
+			Literal::String(value) => Key::String(value.clone()),
+			literal => return RuntimeError::map_key(literal.clone()),
+		})
+	}
+}
+
+/// Keeps its entries in insertion order
+#[derive(Debug, Default)]
+pub struct Map {
+	entries: Vec<(Literal, Literal)>,
+	indices: HashMap<Key, usize>,
+}
+
+impl Map {
+	pub fn get(&self, key: &Literal) -> Result<Literal, RuntimeError> {
+		match self.indices.get(&Key::new(key)?) {
+			Some(&index) => Ok(self.entries[index].1.clone()),
+			None => RuntimeError::missing_key(key.clone()),
+		}
+	}
+
+	/// Overwriting an existing key keeps its position
+	pub fn insert(&mut self, key: Literal, value: Literal) -> Result<(), RuntimeError> {
+		match self.indices.get(&Key::new(&key)?) {
+			Some(&index) => self.entries[index].1 = value,
+			None => {
+				self.indices.insert(Key::new(&key)?, self.entries.len());
+				self.entries.push((key, value));
+			}
+		}
+		Ok(())
+	}
+
+	pub fn keys(&self) -> impl Iterator<Item = &Literal> {
+		self.entries.iter().map(|(key, _)| key)
+	}
+
+	/// Maps are equal if they have the same keys and `equal` holds for the values of each,
+	/// no matter in which order
+	pub fn equals(&self, other: &Self, mut equal: impl FnMut(&Literal, &Literal) -> bool) -> bool {
+		self.entries.len() == other.entries.len()
+			&& (self.indices.iter()).all(|(key, &index)| match other.indices.get(key) {
+				Some(&other_index) => equal(&self.entries[index].1, &other.entries[other_index].1),
+				None => false,
+			})
+	}
+}
+
+impl PartialEq for Map {
+	fn eq(&self, other: &Self) -> bool {
+		self.equals(other, |l, r| l == r)
+	}
+}
+
+impl Display for Map {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "#{{")?;
+		for (i, (key, value)) in self.entries.iter().enumerate() {
+			let separator = if i == 0 { " " } else { ", " };
+			write!(f, "{separator}{key}: {value}")?;
+		}
+		if self.entries.is_empty() {
+			write!(f, "}}")
+		} else {
+			write!(f, " }}")
+		}
+	}
+}
@@ -1,171 +1,1175 @@
-use crate::expr::{BinaryOp, Expr, Stmt, UnaryOp};
+use crate::bigint::BigInt;
+use crate::decimal::Decimal;
+use crate::expr::{
+	AssignTarget, BinaryOp, Block, Expr, LogicalOp, MatchArm, Pattern, Stmt, UnaryOp,
+};
 use crate::literal::Literal::{self, *};
+use crate::literal::Range;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
 
+mod enums;
 mod env;
 mod error;
+mod function;
+mod instance;
+mod interrupt;
+mod map;
+mod native;
+pub use enums::{Enum, Variant};
 use env::Env;
 use error::RuntimeError;
+pub use function::Function;
+pub use instance::{Instance, Struct};
+use interrupt::Interrupt;
+pub use map::Map;
+pub use native::Native;
+
+/// Calls that can be nested before recursion is stopped with an error,
+/// the stack of the interpreter thread has room for a lot more
+const MAX_CALL_DEPTH: usize = 1000;
+
+/// Addresses of the pairs of shared values that are being compared further up
+type Visiting = Vec<(*const (), *const ())>;
 
 pub struct Interpreter {
 	env: Env,
+	/// Number of function calls that have not returned yet
+	depth: usize,
+	/// Divides integers into decimals instead of floats, set by `--decimal`
+	decimals: bool,
 }
 
 impl Interpreter {
 	pub fn new() -> Self {
-		Self { env: Env::new() }
+		let env = Env::new();
+		for native in native::NATIVES {
+			env.declare(native.name.into(), Literal::Native(native), false);
+		}
+		Self {
+			env,
+			depth: 0,
+			decimals: false,
+		}
+	}
+
+	pub fn with_decimals(mut self, decimals: bool) -> Self {
+		self.decimals = decimals;
+		self
 	}
 
 	pub fn eval(&mut self, ast: Vec<Stmt>) -> Result<(), RuntimeError> {
-		for statement in ast {
-			self.statement(statement)?;
+		for statement in &ast {
+			match self.statement(statement) {
+				Ok(()) => {}
+				Err(Interrupt::Error(err)) => return Err(err),
+				Err(interrupt) => {
+					unreachable!("Parser should not allow {interrupt:?} outside of a loop or function")
+				}
+			}
 		}
 		Ok(())
 	}
 
-	fn statement(&mut self, stmt: Stmt) -> Result<(), RuntimeError> {
+	fn statement(&mut self, stmt: &Stmt) -> Result<(), Interrupt> {
 		match stmt {
 			Stmt::Expr(expr) => {
 				self.expr(expr)?;
 			}
-			Stmt::Print(expr) => match self.expr(expr)? {
-				Number(n) => println!("{n}"),
-				String(s) => println!("{s}"),
-				True => println!("true"),
-				False => println!("false"),
-			},
+			Stmt::Print(expr) => println!("{}", self.expr(expr)?),
 			Stmt::Var {
 				name,
 				initializer,
 				mutable,
 			} => {
 				let value = self.expr(initializer)?;
-				self.env.declare(name, value, mutable);
+				self.env.declare(name.clone(), value, *mutable);
+			}
+			Stmt::Destructure {
+				names,
+				initializer,
+				mutable,
+			} => {
+				let values = match self.expr(initializer)? {
+					Tuple(values) => values,
+					value => RuntimeError::destructure(value)?,
+				};
+				if values.len() != names.len() {
+					RuntimeError::destructure_arity(names.len(), values.len())?;
+				}
+				for (name, value) in names.iter().zip(values.iter()) {
+					if let Some(name) = name {
+						self.env.declare(name.clone(), value.clone(), *mutable);
+					}
+				}
+			}
+			Stmt::Fun(decl) => {
+				let function = Function {
+					decl: decl.clone(),
+					closure: self.env.clone(),
+				};
+				self.env.declare(
+					decl.name.clone(),
+					Literal::Function(Rc::new(function)),
+					false,
+				);
+			}
+			Stmt::Struct {
+				name,
+				parent,
+				fields,
+			} => {
+				let parent = match parent {
+					Some(parent) => match self.env.get(parent)? {
+						Literal::Struct(class) => Some(class),
+						value => RuntimeError::not_a_struct(parent, value)?,
+					},
+					None => None,
+				};
+				let mut all_fields = match &parent {
+					Some(parent) => parent.fields.clone(),
+					None => Vec::new(),
+				};
+				for field in fields {
+					if all_fields.contains(field) {
+						RuntimeError::redeclared_field(name, field)?;
+					}
+					all_fields.push(field.clone());
+				}
+
+				let class = Struct {
+					name: name.clone(),
+					parent,
+					fields: all_fields,
+					methods: RefCell::new(HashMap::new()),
+				};
+				self
+					.env
+					.declare(name.clone(), Literal::Struct(Rc::new(class)), false);
+			}
+			Stmt::Enum { name, variants } => {
+				let class = Enum {
+					name: name.clone(),
+					variants: variants
+						.iter()
+						.map(|variant| (variant.name.clone(), variant.fields.len()))
+						.collect(),
+				};
+				self
+					.env
+					.declare(name.clone(), Literal::Enum(Rc::new(class)), false);
+			}
+			Stmt::Impl { name, methods } => {
+				let class = match self.env.get(name)? {
+					Literal::Struct(class) => class,
+					value => RuntimeError::not_a_struct(name, value)?,
+				};
+				let closure = self.env.child();
+				if let Some(parent) = &class.parent {
+					closure.declare("super".into(), Literal::Struct(parent.clone()), false);
+				}
+				for decl in methods {
+					let method = Function {
+						decl: decl.clone(),
+						closure: closure.clone(),
+					};
+					(class.methods.borrow_mut()).insert(decl.name.clone(), Rc::new(method));
+				}
 			}
-			Stmt::Block(statements) => self.eval_block(statements)?,
 		}
 		Ok(())
 	}
 
-	fn eval_block(&mut self, statements: Vec<Stmt>) -> Result<(), RuntimeError> {
-		self.env.push_scope();
-		for statement in statements {
-			self.statement(statement)?;
+	/// Runs `f` with `env` as the current scope and restores the previous one afterwards
+	fn scoped<T>(&mut self, env: Env, f: impl FnOnce(&mut Self) -> T) -> T {
+		let previous = std::mem::replace(&mut self.env, env);
+		let result = f(self);
+		self.env = previous;
+		result
+	}
+
+	/// Blocks without a trailing expression evaluate to `()`
+	fn block(&mut self, block: &Block) -> Result<Literal, Interrupt> {
+		self.scoped(self.env.child(), |this| {
+			for statement in &block.stmts {
+				this.statement(statement)?;
+			}
+			match &block.value {
+				Some(expr) => this.expr(expr),
+				None => Ok(Unit),
+			}
+		})
+	}
+
+	fn if_expr(
+		&mut self,
+		condition: &Expr,
+		then_branch: &Block,
+		else_branch: Option<&Expr>,
+	) -> Result<Literal, Interrupt> {
+		if self.condition(condition)? {
+			self.block(then_branch)
+		} else if let Some(else_branch) = else_branch {
+			self.expr(else_branch)
+		} else {
+			Ok(Unit)
+		}
+	}
+
+	fn while_expr(&mut self, condition: &Expr, body: &Block) -> Result<Literal, Interrupt> {
+		while self.condition(condition)? {
+			match self.block(body) {
+				Ok(_) | Err(Interrupt::Continue) => {}
+				Err(Interrupt::Break(_)) => break,
+				Err(err) => return Err(err),
+			}
+		}
+		Ok(Unit)
+	}
+
+	fn loop_expr(&mut self, body: &Block) -> Result<Literal, Interrupt> {
+		loop {
+			match self.block(body) {
+				Ok(_) | Err(Interrupt::Continue) => {}
+				Err(Interrupt::Break(value)) => return Ok(value),
+				Err(err) => return Err(err),
+			}
+		}
+	}
+
+	fn for_expr(&mut self, name: &str, iterable: &Expr, body: &Block) -> Result<Literal, Interrupt> {
+		let values: Box<dyn Iterator<Item = Literal>> = match self.expr(iterable)? {
+			Range(range) => Box::new(range.iter().map(Int)),
+			// Copied first, so changing the list inside the body does not affect the loop
+			List(items) => Box::new(items.borrow().clone().into_iter()),
+			String(string) => Box::new(string.chars().collect::<Vec<_>>().into_iter().map(Char)),
+			Literal::Map(map) => Box::new(map.borrow().keys().cloned().collect::<Vec<_>>().into_iter()),
+			value => RuntimeError::iteration(value)?,
+		};
+
+		for value in values {
+			// Every iteration gets a fresh scope for the loop variable
+			let env = self.env.child();
+			env.declare(name.into(), value, false);
+			let result = self.scoped(env, |this| this.block(body));
+
+			match result {
+				Ok(_) | Err(Interrupt::Continue) => {}
+				Err(Interrupt::Break(_)) => break,
+				Err(err) => return Err(err),
+			}
+		}
+		Ok(Unit)
+	}
+
+	/// Fields shadow methods, methods are bound to the instance they are accessed on
+	fn get(object: Literal, name: &str) -> Result<Literal, RuntimeError> {
+		match &object {
+			Literal::Instance(instance) => {
+				let instance = instance.borrow();
+				if let Some(value) = instance.get(name) {
+					return Ok(value);
+				}
+				match instance.class.find_method(name) {
+					Some(method) => Ok(Literal::Function(Rc::new(method.bind(object.clone())))),
+					None => RuntimeError::unknown_member(&instance.class.name, name),
+				}
+			}
+			// Methods without `this`, like constructors, can be called on the struct itself
+			Literal::Struct(class) => match class.find_method(name) {
+				Some(method) => Ok(Literal::Function(method)),
+				None => RuntimeError::unknown_member(&class.name, name),
+			},
+			_ => RuntimeError::field_access(object, name),
+		}
+	}
+
+	/// Resolves `super.name` starting at the parent of the struct the current method belongs to
+	fn super_method(&self, name: &str) -> Result<Literal, RuntimeError> {
+		let parent = match self.env.get("super") {
+			Ok(Literal::Struct(parent)) => parent,
+			_ => return RuntimeError::no_parent(),
+		};
+		match parent.find_method(name) {
+			Some(method) => Ok(Literal::Function(Rc::new(
+				method.bind(self.env.get("this")?),
+			))),
+			None => RuntimeError::unknown_method(&parent.name, name),
+		}
+	}
+
+	fn assign(&mut self, target: &AssignTarget, value: Literal) -> Result<(), Interrupt> {
+		match target {
+			AssignTarget::Var(name) => self.env.set(name, value)?,
+			AssignTarget::Field(object, name) => {
+				self.check_mutable(object, &format!("field `{name}`"), None)?;
+				match self.expr(object)? {
+					Literal::Instance(instance) => instance.borrow_mut().set(name, value)?,
+					object => RuntimeError::field_access(object, name)?,
+				}
+			}
+			AssignTarget::Index(object, index) => {
+				self.check_mutable(object, "an element", None)?;
+				let object = self.expr(object)?;
+				let index = self.expr(index)?;
+				Self::set_index(object, index, value)?;
+			}
 		}
-		self.env.pop_scope();
 		Ok(())
 	}
 
-	fn expr(&mut self, expr: Expr) -> Result<Literal, RuntimeError> {
+	/// Reads the current value of the target and writes back the result of applying `op`,
+	/// constants are rejected before anything is evaluated
+	fn compound_assign(
+		&mut self,
+		target: &AssignTarget,
+		op: &BinaryOp,
+		expr: &Expr,
+	) -> Result<Literal, Interrupt> {
+		match target {
+			AssignTarget::Var(name) => {
+				if !self.env.is_mutable(name)? {
+					RuntimeError::compound_assignment(name, op)?;
+				}
+				let current = self.env.get(name)?;
+				let right = self.expr(expr)?;
+				let value = self.binary(current, op, right)?;
+				self.env.set(name, value.clone())?;
+				Ok(value)
+			}
+			AssignTarget::Field(object, name) => {
+				self.check_mutable(object, &format!("field `{name}`"), Some(op))?;
+				let instance = match self.expr(object)? {
+					Literal::Instance(instance) => instance,
+					object => RuntimeError::field_access(object, name)?,
+				};
+				let current = match instance.borrow().get(name) {
+					Some(current) => current,
+					None => RuntimeError::unknown_field(&instance.borrow().class.name, name)?,
+				};
+				let right = self.expr(expr)?;
+				let value = self.binary(current, op, right)?;
+				instance.borrow_mut().set(name, value.clone())?;
+				Ok(value)
+			}
+			AssignTarget::Index(object, index) => {
+				self.check_mutable(object, "an element", Some(op))?;
+				let object = self.expr(object)?;
+				let index = self.expr(index)?;
+				let current = Self::index(object.clone(), index.clone())?;
+				let right = self.expr(expr)?;
+				let value = self.binary(current, op, right)?;
+				Self::set_index(object, index, value.clone())?;
+				Ok(value)
+			}
+		}
+	}
+
+	/// Fields and elements can only be assigned to if the variable holding the value is mutable,
+	/// methods can always assign to the fields of `this`
+	fn check_mutable(
+		&self,
+		object: &Expr,
+		member: &str,
+		op: Option<&BinaryOp>,
+	) -> Result<(), RuntimeError> {
+		match object {
+			Expr::Var(name) if !self.env.is_mutable(name)? => {
+				RuntimeError::member_assignment(name, member, op)
+			}
+			Expr::Get(object, name) => self.check_mutable(object, &format!("field `{name}`"), op),
+			Expr::Index(object, _) => self.check_mutable(object, "an element", op),
+			Expr::Group(object) => self.check_mutable(object, member, op),
+			_ => Ok(()),
+		}
+	}
+
+	/// Indexing with a range copies the selected elements into a new list
+	fn index(object: Literal, index: Literal) -> Result<Literal, RuntimeError> {
+		match (&object, &index) {
+			(List(items), Int(index)) => {
+				let items = items.borrow();
+				Ok(items[Self::list_index(*index, items.len())?].clone())
+			}
+			// Strings are indexed by Unicode scalar value, not by byte
+			(String(string), Int(index)) => {
+				let index = Self::list_index(*index, string.chars().count())?;
+				Ok(Char(string.chars().nth(index).unwrap()))
+			}
+			(String(string), Range(range)) => {
+				let chars: Vec<char> = string.chars().collect();
+				let slice = range
+					.iter()
+					.map(|index| Ok(chars[Self::list_index(index, chars.len())?]))
+					.collect::<Result<std::string::String, RuntimeError>>()?;
+				Ok(String(slice))
+			}
+			(Tuple(items), Int(index)) => Ok(items[Self::list_index(*index, items.len())?].clone()),
+			(List(items), Range(range)) => {
+				let items = items.borrow();
+				let slice = range
+					.iter()
+					.map(|index| Ok(items[Self::list_index(index, items.len())?].clone()))
+					.collect::<Result<Vec<_>, RuntimeError>>()?;
+				Ok(List(Rc::new(RefCell::new(slice))))
+			}
+			(Literal::Map(map), key) => map.borrow().get(key),
+			_ => RuntimeError::index(object, index),
+		}
+	}
+
+	fn set_index(object: Literal, index: Literal, value: Literal) -> Result<(), RuntimeError> {
+		match (&object, &index) {
+			(List(items), Int(index)) => {
+				let mut items = items.borrow_mut();
+				let index = Self::list_index(*index, items.len())?;
+				items[index] = value;
+				Ok(())
+			}
+			(Literal::Map(map), _) => map.borrow_mut().insert(index, value),
+			_ => RuntimeError::index(object, index),
+		}
+	}
+
+	/// Negative indices count from the end of the list
+	fn list_index(index: i64, len: usize) -> Result<usize, RuntimeError> {
+		let resolved = if index < 0 { index + len as i64 } else { index };
+		if (0..len as i64).contains(&resolved) {
+			Ok(resolved as usize)
+		} else {
+			RuntimeError::index_out_of_bounds(index, len)
+		}
+	}
+
+	fn struct_literal(
+		&mut self,
+		name: &str,
+		fields: &[(std::string::String, Expr)],
+	) -> Result<Literal, Interrupt> {
+		let class = match self.env.get(name)? {
+			Literal::Struct(class) => class,
+			value => RuntimeError::not_a_struct(name, value)?,
+		};
+		let values = fields
+			.iter()
+			.map(|(field, expr)| Ok((field.clone(), self.expr(expr)?)))
+			.collect::<Result<Vec<_>, Interrupt>>()?;
+
+		Ok(Literal::Instance(class.instantiate(values)?))
+	}
+
+	fn call(&mut self, callee: &Expr, args: &[Expr]) -> Result<Literal, Interrupt> {
+		let callee = self.expr(callee)?;
+		let args = args
+			.iter()
+			.map(|arg| self.expr(arg))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		match callee {
+			Literal::Function(function) => self.call_function(&function, args),
+			Literal::Native(native) => {
+				if args.len() != native.arity {
+					RuntimeError::arity(native.name, native.arity, args.len())?;
+				}
+				Ok((native.fun)(&args)?)
+			}
+			Literal::Constructor(class, index) => {
+				Ok(Literal::Variant(Rc::new(class.construct(index, args)?)))
+			}
+			callee => Ok(RuntimeError::call(callee)?),
+		}
+	}
+
+	fn call_function(
+		&mut self,
+		function: &Function,
+		args: Vec<Literal>,
+	) -> Result<Literal, Interrupt> {
+		if args.len() != function.arity() {
+			RuntimeError::arity(&function.decl.name, function.arity(), args.len())?;
+		}
+
+		if self.depth == MAX_CALL_DEPTH {
+			RuntimeError::recursion(MAX_CALL_DEPTH)?;
+		}
+
+		let env = function.closure.child();
+		for (param, arg) in function.decl.params.iter().zip(args) {
+			env.declare(param.clone(), arg, false);
+		}
+
+		self.depth += 1;
+		let result = self.scoped(env, |this| this.block(&function.decl.body));
+		self.depth -= 1;
+		match result {
+			Err(Interrupt::Return(value)) => Ok(value),
+			result => result,
+		}
+	}
+
+	fn range(
+		&mut self,
+		start: &Expr,
+		end: &Expr,
+		step: Option<&Expr>,
+		inclusive: bool,
+	) -> Result<Literal, Interrupt> {
+		let (start, end) = match (self.expr(start)?, self.expr(end)?) {
+			(Int(start), Int(end)) => (start, end),
+			(start, end) => RuntimeError::range(start, end)?,
+		};
+		let step = match step {
+			Some(step) => match self.expr(step)? {
+				Int(step) if step != 0 => step,
+				step => RuntimeError::range_step(step)?,
+			},
+			None => 1,
+		};
+
+		Ok(Range(Range {
+			start,
+			end,
+			step,
+			inclusive,
+		}))
+	}
+
+	fn match_expr(&mut self, value: &Expr, arms: &[MatchArm]) -> Result<Literal, Interrupt> {
+		let value = self.expr(value)?;
+
+		for arm in arms {
+			// Every arm gets its own scope for the variables bound by its pattern
+			let env = self.env.child();
+			if !Self::match_pattern(&arm.pattern, &value, &env) {
+				continue;
+			}
+			let result = self.scoped(env, |this| {
+				if let Some(guard) = &arm.guard {
+					if !this.condition(guard)? {
+						return Ok(None);
+					}
+				}
+				this.expr(&arm.body).map(Some)
+			})?;
+			if let Some(value) = result {
+				return Ok(value);
+			}
+		}
+
+		Ok(RuntimeError::no_match(value)?)
+	}
+
+	fn match_pattern(pattern: &Pattern, value: &Literal, env: &Env) -> bool {
+		match pattern {
+			Pattern::Literal(literal) => Self::equals(literal, value),
+			Pattern::Range {
+				start,
+				end,
+				inclusive,
+			} => match (Self::compare(start, value), Self::compare(value, end)) {
+				(Ok(Some(Ordering::Less | Ordering::Equal)), Ok(Some(Ordering::Less))) => true,
+				(Ok(Some(Ordering::Less | Ordering::Equal)), Ok(Some(Ordering::Equal))) => *inclusive,
+				_ => false,
+			},
+			Pattern::Variant {
+				path: (class, name),
+				values: patterns,
+			} => match value {
+				Literal::Variant(variant)
+					if &variant.class.name == class
+						&& variant.name() == name
+						&& variant.values.len() == patterns.len() =>
+				{
+					(patterns.iter().zip(&variant.values))
+						.all(|(pattern, value)| Self::match_pattern(pattern, value, env))
+				}
+				_ => false,
+			},
+			Pattern::Or(patterns) => patterns
+				.iter()
+				.any(|pattern| Self::match_pattern(pattern, value, env)),
+			Pattern::Binding(name) => {
+				env.declare(name.clone(), value.clone(), false);
+				true
+			}
+			Pattern::Wildcard => true,
+		}
+	}
+
+	/// Conditions have to be booleans, there is no implicit truthiness
+	fn condition(&mut self, expr: &Expr) -> Result<bool, Interrupt> {
+		Ok(match self.expr(expr)? {
+			True => true,
+			False => false,
+			literal => RuntimeError::condition(literal)?,
+		})
+	}
+
+	fn expr(&mut self, expr: &Expr) -> Result<Literal, Interrupt> {
 		Ok(match expr {
-			Expr::Var(name) => self.env.get(&name)?.clone(),
-			Expr::Assign(name, expr) => {
-				let value = self.expr(*expr)?;
-				self.env.set(&name, value.clone())?;
+			Expr::Var(name) => self.env.get(name)?,
+			Expr::Assign(target, expr) => {
+				let value = self.expr(expr)?;
+				self.assign(target, value.clone())?;
 				value
 			}
-			Expr::Literal(literal) => literal,
-			Expr::Group(expr) => self.expr(*expr)?,
-			Expr::Unary(op, expr) => self.unary(op, *expr)?,
-			Expr::Binary(expr_l, op, expr_r) => self.binary(*expr_l, op, *expr_r)?,
+			Expr::CompoundAssign(target, op, expr) => self.compound_assign(target, op, expr)?,
+			Expr::Tuple(items) => {
+				let items = items
+					.iter()
+					.map(|item| self.expr(item))
+					.collect::<Result<Vec<_>, _>>()?;
+				Tuple(Rc::new(items))
+			}
+			Expr::List(items) => {
+				let items = items
+					.iter()
+					.map(|item| self.expr(item))
+					.collect::<Result<Vec<_>, _>>()?;
+				List(Rc::new(RefCell::new(items)))
+			}
+			Expr::Map(entries) => {
+				let mut map = Map::default();
+				for (key, value) in entries {
+					let key = self.expr(key)?;
+					map.insert(key, self.expr(value)?)?;
+				}
+				Literal::Map(Rc::new(RefCell::new(map)))
+			}
+			Expr::Index(object, index) => {
+				let object = self.expr(object)?;
+				let index = self.expr(index)?;
+				Self::index(object, index)?
+			}
+			Expr::Get(object, name) => {
+				let object = self.expr(object)?;
+				Self::get(object, name)?
+			}
+			Expr::This => self.env.get("this")?,
+			Expr::Path(name, variant) => match self.env.get(name)? {
+				Literal::Enum(class) => {
+					let index = class.find_variant(variant)?;
+					match class.variants[index] {
+						(_, 0) => Literal::Variant(Rc::new(class.construct(index, Vec::new())?)),
+						_ => Literal::Constructor(class, index),
+					}
+				}
+				value => RuntimeError::path(value, variant)?,
+			},
+			Expr::Super(name) => self.super_method(name)?,
+			Expr::StructLiteral { name, fields } => self.struct_literal(name, fields)?,
+			Expr::Literal(literal) => literal.clone(),
+			Expr::Interpolation(parts) => {
+				let mut value = std::string::String::new();
+				for part in parts {
+					value.push_str(&self.expr(part)?.to_string());
+				}
+				String(value)
+			}
+			Expr::Group(expr) => self.expr(expr)?,
+			Expr::Unary(op, expr) => {
+				let right = self.expr(expr)?;
+				Self::unary(op, right)?
+			}
+			Expr::Binary(expr_l, op, expr_r) => {
+				let left = self.expr(expr_l)?;
+				let right = self.expr(expr_r)?;
+				self.binary(left, op, right)?
+			}
+			Expr::Logical(expr_l, op, expr_r) => self.logical(expr_l, op, expr_r)?,
+			Expr::Break(expr) => {
+				let value = match expr {
+					Some(expr) => self.expr(expr)?,
+					None => Unit,
+				};
+				return Err(Interrupt::Break(value));
+			}
+			Expr::Continue => return Err(Interrupt::Continue),
+			Expr::Return(expr) => {
+				let value = match expr {
+					Some(expr) => self.expr(expr)?,
+					None => Unit,
+				};
+				return Err(Interrupt::Return(value));
+			}
+			Expr::Range {
+				start,
+				end,
+				step,
+				inclusive,
+			} => self.range(start, end, step.as_deref(), *inclusive)?,
+			Expr::Block(block) => self.block(block)?,
+			Expr::If {
+				condition,
+				then_branch,
+				else_branch,
+			} => self.if_expr(condition, then_branch, else_branch.as_deref())?,
+			Expr::While { condition, body } => self.while_expr(condition, body)?,
+			Expr::Loop(body) => self.loop_expr(body)?,
+			Expr::For {
+				name,
+				iterable,
+				body,
+			} => self.for_expr(name, iterable, body)?,
+			Expr::Call(callee, args) => self.call(callee, args)?,
+			Expr::Match { value, arms } => self.match_expr(value, arms)?,
 		})
 	}
 
-	fn unary(&mut self, op: UnaryOp, expr: Expr) -> Result<Literal, RuntimeError> {
-		let right = self.expr(expr)?;
+	/// Only evaluates the right side if the left one does not already decide the result
+	fn logical(
+		&mut self,
+		expr_l: &Expr,
+		op: &LogicalOp,
+		expr_r: &Expr,
+	) -> Result<Literal, Interrupt> {
+		match (op, self.expr(expr_l)?) {
+			(LogicalOp::And, False) => return Ok(False),
+			(LogicalOp::Or, True) => return Ok(True),
+			(_, True | False) => {}
+			(op, literal) => RuntimeError::logical(op, literal)?,
+		}
+
+		Ok(match self.expr(expr_r)? {
+			value @ (True | False) => value,
+			literal => RuntimeError::logical(op, literal)?,
+		})
+	}
 
+	fn unary(op: &UnaryOp, right: Literal) -> Result<Literal, RuntimeError> {
 		match (op, right) {
 			(UnaryOp::Not, True) => Ok(False),
 			(UnaryOp::Not, False) => Ok(True),
+			(UnaryOp::Neg, Int(n)) => match n.checked_neg() {
+				Some(n) => Ok(Int(n)),
+				None => Ok((-&BigInt::from(n)).into()),
+			},
+			(UnaryOp::Neg, Literal::BigInt(n)) => Ok((-&*n).into()),
 			(UnaryOp::Neg, Number(n)) => Ok(Number(-n)),
+			(UnaryOp::Neg, Literal::Decimal(n)) => Ok(Literal::Decimal(Rc::new(-&*n))),
+			(UnaryOp::BitNot, value @ (Int(_) | Literal::BigInt(_) | Number(_))) => {
+				Ok(Int(!Self::integral(&value, op)?))
+			}
 			(op, literal) => RuntimeError::unary(op, literal),
 		}
 	}
 
-	fn binary(&mut self, expr_l: Expr, op: BinaryOp, expr_r: Expr) -> Result<Literal, RuntimeError> {
-		let left = self.expr(expr_l)?;
-		let right = self.expr(expr_r)?;
-
+	fn binary(&self, left: Literal, op: &BinaryOp, right: Literal) -> Result<Literal, RuntimeError> {
+		// Only these operations turn integers into floats
+		let left = match (&left, op, &right) {
+			(Int(_) | Literal::BigInt(_), BinaryOp::Div, Int(_) | Literal::BigInt(_))
+			| (Int(_) | Literal::BigInt(_), BinaryOp::Pow, Int(i64::MIN..=-1))
+				if self.decimals =>
+			{
+				Literal::Decimal(Rc::new(Self::exact(&left).expect("Integers are exact")))
+			}
+			_ => left,
+		};
+		if let Some(result) = Self::decimal(&left, op, &right) {
+			return result;
+		}
 		// TODO: Clean this up evme more!
 		match op {
-			BinaryOp::Equal => Ok(if left == right { True } else { False }),
-			BinaryOp::NotEqual => Ok(if left != right { True } else { False }),
-			BinaryOp::Greater => Self::comparison(left, right, |l, r| l > r),
-			BinaryOp::GreaterEqual => Self::comparison(left, right, |l, r| l >= r),
-			BinaryOp::Less => Self::comparison(left, right, |l, r| l < r),
-			BinaryOp::LessEqual => Self::comparison(left, right, |l, r| l <= r),
+			BinaryOp::Equal => Ok(if Self::equals(&left, &right) {
+				True
+			} else {
+				False
+			}),
+			BinaryOp::NotEqual => Ok(if Self::equals(&left, &right) {
+				False
+			} else {
+				True
+			}),
+			BinaryOp::Greater => Self::comparison(left, right, |ord| ord.is_gt()),
+			BinaryOp::GreaterEqual => Self::comparison(left, right, |ord| ord.is_ge()),
+			BinaryOp::Less => Self::comparison(left, right, |ord| ord.is_lt()),
+			BinaryOp::LessEqual => Self::comparison(left, right, |ord| ord.is_le()),
 			BinaryOp::Add => match (&left, &right) {
-				(Number(l), Number(r)) => Ok(Number(l + r)),
-				(Number(l), String(r)) => {
-					let mut value = l.to_string();
-					value.push_str(r);
-					Ok(String(value))
-				}
-				(String(l), r) => {
-					let mut value = l.clone();
-
-					if let String(ref inner) = r {
-						value.push_str(inner);
-					}
-					if let Number(inner) = r {
-						value.push_str(&inner.to_string());
-					}
-					if &True == r {
-						value.push_str("true");
-					}
-					if &False == r {
-						value.push_str("false");
-					}
-
-					Ok(String(value))
+				(String(_), other) | (other, String(_))
+					if matches!(
+						other,
+						String(_)
+							| Char(_)
+							| Int(_)
+							| Literal::BigInt(_)
+							| Number(_)
+							| Literal::Decimal(_)
+							| True | False
+					) =>
+				{
+					Ok(String(format!("{left}{right}")))
 				}
-				(True, String(inner)) => {
-					let mut value = "true".to_string();
-					value.push_str(inner);
-					Ok(String(value))
+				(Char(l), Char(r)) => Ok(String(format!("{l}{r}"))),
+				_ => Self::algebraic(
+					left,
+					op,
+					right,
+					i64::checked_add,
+					|l, r| Some(l + r),
+					|l, r| l + r,
+					RuntimeError::addition,
+				),
+			},
+			BinaryOp::Sub => Self::algebraic(
+				left,
+				op,
+				right,
+				i64::checked_sub,
+				|l, r| Some(l - r),
+				|l, r| l - r,
+				RuntimeError::substraction,
+			),
+			BinaryOp::Mul => Self::algebraic(
+				left,
+				op,
+				right,
+				i64::checked_mul,
+				BigInt::checked_mul,
+				|l, r| l * r,
+				RuntimeError::multiplication,
+			),
+			BinaryOp::Div | BinaryOp::FloorDiv | BinaryOp::Rem
+				if matches!(right, Int(0)) || right == Number(0.0) =>
+			{
+				Err(RuntimeError::DivideByZero)
+			}
+			// Integers are promoted, so `/` is float division for every number.
+			// `~/` is the exact division for integers of any size
+			BinaryOp::Div => match (Self::float(&left), Self::float(&right)) {
+				(Some(l), Some(r)) => Ok(Number(l / r)),
+				_ => RuntimeError::division(left, right),
+			},
+			BinaryOp::FloorDiv => Self::algebraic(
+				left,
+				op,
+				right,
+				Self::floor_div,
+				BigInt::floor_div,
+				|l, r| (l / r).floor(),
+				RuntimeError::division,
+			),
+			BinaryOp::Rem => Self::algebraic(
+				left,
+				op,
+				right,
+				i64::checked_rem,
+				|l, r| Some(l.div_rem(r)?.1),
+				|l, r| l % r,
+				RuntimeError::remainder,
+			),
+			// Negative integer exponents produce a float
+			BinaryOp::Pow => match (left, right) {
+				// Same as `1 / 0`
+				(Int(0), Int(r)) if r < 0 => Err(RuntimeError::DivideByZero),
+				(left @ (Int(_) | Literal::BigInt(_)), Int(r)) if r < 0 => {
+					Ok(Number(Self::float(&left).unwrap().powf(r as f64)))
 				}
-				(False, String(inner)) => {
-					let mut value = "false".to_string();
-					value.push_str(inner);
-					Ok(String(value))
+				(left, right) => Self::algebraic(
+					left,
+					op,
+					right,
+					|l, r| l.checked_pow(u32::try_from(r).ok()?),
+					|l, r| l.pow(u32::try_from(r.to_i64()?).ok()?),
+					f64::powf,
+					RuntimeError::exponentiation,
+				),
+			},
+			BinaryOp::BitAnd => Self::bitwise(left, op, right, |l, r| Ok(l & r)),
+			BinaryOp::BitOr => Self::bitwise(left, op, right, |l, r| Ok(l | r)),
+			BinaryOp::BitXor => Self::bitwise(left, op, right, |l, r| Ok(l ^ r)),
+			BinaryOp::Shl => Self::bitwise(left, op, right, |l, r| Ok(l << Self::shift_amount(r)?)),
+			BinaryOp::Shr => Self::bitwise(left, op, right, |l, r| Ok(l >> Self::shift_amount(r)?)),
+		}
+	}
+
+	/// Arithmetic with at least one decimal, they only mix with integers so the result stays exact.
+	/// `None` for every other operation and operand, which are handled like usual
+	fn decimal(
+		left: &Literal,
+		op: &BinaryOp,
+		right: &Literal,
+	) -> Option<Result<Literal, RuntimeError>> {
+		if !matches!(left, Literal::Decimal(_)) && !matches!(right, Literal::Decimal(_)) {
+			return None;
+		}
+		let (l, r) = (Self::exact(left)?, Self::exact(right)?);
+		let result = match op {
+			BinaryOp::Add => l.checked_add(&r),
+			BinaryOp::Sub => l.checked_sub(&r),
+			BinaryOp::Mul => l.checked_mul(&r),
+			BinaryOp::Div | BinaryOp::FloorDiv | BinaryOp::Rem if r.is_zero() => {
+				return Some(Err(RuntimeError::DivideByZero))
+			}
+			BinaryOp::Div => l.checked_div(&r),
+			BinaryOp::FloorDiv => l.floor_div(&r),
+			BinaryOp::Rem => l.checked_rem(&r),
+			BinaryOp::Pow => match right {
+				Int(exponent) if *exponent < 0 && l.is_zero() => {
+					return Some(Err(RuntimeError::DivideByZero))
 				}
-				_ => RuntimeError::addition(left, right),
+				Int(exponent) => l.checked_pow(*exponent),
+				_ => return Some(RuntimeError::exponentiation(left.clone(), right.clone())),
 			},
-			BinaryOp::Sub => Self::algebraic(left, right, |l, r| l - r, RuntimeError::substraction),
-			BinaryOp::Mul => Self::algebraic(left, right, |l, r| l - r, RuntimeError::multiplication),
-			BinaryOp::Div if right == Number(0f64) => Err(RuntimeError::DivideByZero),
-			BinaryOp::Div => Self::algebraic(left, right, |l, r| l - r, RuntimeError::division),
+			_ => return None,
+		};
+		Some(match result {
+			Some(value) => Ok(Literal::Decimal(Rc::new(value))),
+			None => RuntimeError::too_large(left, op, right),
+		})
+	}
+
+	fn exact(value: &Literal) -> Option<Decimal> {
+		match value {
+			Literal::Decimal(value) => Some((**value).clone()),
+			_ => Some(Decimal::from(Self::big(value)?)),
 		}
 	}
 
-	fn comparison<F>(left: Literal, right: Literal, cmp_fn: F) -> Result<Literal, RuntimeError>
+	fn bitwise<F>(
+		left: Literal,
+		op: &BinaryOp,
+		right: Literal,
+		bit_fn: F,
+	) -> Result<Literal, RuntimeError>
 	where
-		F: Fn(f64, f64) -> bool,
+		F: Fn(i64, i64) -> Result<i64, RuntimeError>,
 	{
-		if let Number(l) = left {
-			if let Number(r) = right {
-				return Ok(if cmp_fn(l, r) { True } else { False });
+		match (&left, &right) {
+			(Int(_) | Literal::BigInt(_) | Number(_), Int(_) | Literal::BigInt(_) | Number(_)) => {
+				Ok(Int(bit_fn(
+					Self::integral(&left, op)?,
+					Self::integral(&right, op)?,
+				)?))
 			}
+			_ => RuntimeError::bitwise(op, left, right),
 		}
+	}
+
+	/// Floats are only accepted by bitwise operators if they are whole and fit into an `int`
+	fn integral(value: &Literal, op: &dyn Display) -> Result<i64, RuntimeError> {
+		match *value {
+			Int(value) => Ok(value),
+			Number(value)
+				if value.fract() == 0.0 && (i64::MIN as f64..-(i64::MIN as f64)).contains(&value) =>
+			{
+				Ok(value as i64)
+			}
+			Number(value) => RuntimeError::not_integral(op, value),
+			Literal::BigInt(ref value) => RuntimeError::bitwise_range(op, value),
+			_ => unreachable!("Only called with numbers"),
+		}
+	}
+
+	fn shift_amount(amount: i64) -> Result<u32, RuntimeError> {
+		match amount {
+			0..=63 => Ok(amount as u32),
+			_ => RuntimeError::shift(amount),
+		}
+	}
 
-		RuntimeError::comparison(left, right)
+	/// Equality used by `==`, `!=` and literal patterns, integers are compared with floats and decimals by value.
+	/// Tuples, lists, maps, enum values and instances are equal if their items are, so it agrees with `compare`
+	fn equals(left: &Literal, right: &Literal) -> bool {
+		Self::equals_within(left, right, &mut Visiting::new())
 	}
 
-	fn algebraic<F, E>(
+	fn equals_within(left: &Literal, right: &Literal, visiting: &mut Visiting) -> bool {
+		match (left, right) {
+			(Int(_) | Literal::BigInt(_), Number(_))
+			| (Number(_), Int(_) | Literal::BigInt(_))
+			| (Literal::Decimal(_), Int(_) | Literal::BigInt(_) | Literal::Decimal(_))
+			| (Int(_) | Literal::BigInt(_), Literal::Decimal(_))
+			| (Char(_), String(_))
+			| (String(_), Char(_)) => {
+				matches!(Self::compare(left, right), Ok(Some(Ordering::Equal)))
+			}
+			(Tuple(l), Tuple(r)) => Self::items_equal(l, r, visiting),
+			(List(l), List(r)) => Self::visit(l, r, visiting, true, |visiting| {
+				Self::items_equal(&l.borrow(), &r.borrow(), visiting)
+			}),
+			(Variant(l), Variant(r)) => {
+				Rc::ptr_eq(&l.class, &r.class)
+					&& l.name() == r.name()
+					&& Self::items_equal(&l.values, &r.values, visiting)
+			}
+			(Literal::Map(l), Literal::Map(r)) => Self::visit(l, r, visiting, true, |visiting| {
+				(l.borrow()).equals(&r.borrow(), |l, r| Self::equals_within(l, r, visiting))
+			}),
+			(Instance(l), Instance(r)) => Self::visit(l, r, visiting, true, |visiting| {
+				let (l, r) = (l.borrow(), r.borrow());
+				Rc::ptr_eq(&l.class, &r.class)
+					&& (l.class.fields.iter()).all(|name| match (l.get(name), r.get(name)) {
+						(Some(l), Some(r)) => Self::equals_within(&l, &r, visiting),
+						_ => false,
+					})
+			}),
+			_ => left == right,
+		}
+	}
+
+	fn items_equal(left: &[Literal], right: &[Literal], visiting: &mut Visiting) -> bool {
+		left.len() == right.len()
+			&& (left.iter().zip(right)).all(|(l, r)| Self::equals_within(l, r, visiting))
+	}
+
+	/// Orders values of the same kind, integers are only converted to floats when compared with one.
+	///
+	/// Strings are ordered by code point, a char is treated like a string that only contains it.
+	/// `false` comes before `true` and tuples and lists are compared element by element.
+	/// `None` means the values are unordered, like `NaN` with anything
+	fn compare(left: &Literal, right: &Literal) -> Result<Option<Ordering>, RuntimeError> {
+		Self::compare_within(left, right, &mut Visiting::new())
+	}
+
+	fn compare_within(
+		left: &Literal,
+		right: &Literal,
+		visiting: &mut Visiting,
+	) -> Result<Option<Ordering>, RuntimeError> {
+		Ok(match (left, right) {
+			(Unit, Unit) => Some(Ordering::Equal),
+			(True | False, True | False) => Some((left == &True).cmp(&(right == &True))),
+			(Char(l), Char(r)) => Some(l.cmp(r)),
+			(String(l), String(r)) => Some(l.cmp(r)),
+			(Char(l), String(r)) => Some(l.to_string().cmp(r)),
+			(String(l), Char(r)) => Some(l.cmp(&r.to_string())),
+			(Int(l), Int(r)) => Some(l.cmp(r)),
+			(Int(_) | Literal::BigInt(_), Int(_) | Literal::BigInt(_)) => {
+				Self::big(left).partial_cmp(&Self::big(right))
+			}
+			(Int(_) | Literal::BigInt(_), Number(r)) => Self::compare_with_float(left, *r),
+			(Number(l), Int(_) | Literal::BigInt(_)) => {
+				Self::compare_with_float(right, *l).map(Ordering::reverse)
+			}
+			(Number(l), Number(r)) => l.partial_cmp(r),
+			(Literal::Decimal(_), Int(_) | Literal::BigInt(_) | Literal::Decimal(_))
+			| (Int(_) | Literal::BigInt(_), Literal::Decimal(_)) => {
+				Self::exact(left).partial_cmp(&Self::exact(right))
+			}
+			(Tuple(l), Tuple(r)) => Self::compare_items(l, r, visiting)?,
+			(List(l), List(r)) => Self::visit(l, r, visiting, Ok(Some(Ordering::Equal)), |visiting| {
+				Self::compare_items(&l.borrow(), &r.borrow(), visiting)
+			})?,
+			_ => RuntimeError::comparison(left.clone(), right.clone())?,
+		})
+	}
+
+	/// Exact for whole floats, so it agrees with map keys. Integers can not be equal to a fraction,
+	/// which keeps converting them to floats correct for the rest
+	fn compare_with_float(int: &Literal, float: f64) -> Option<Ordering> {
+		match (Self::big(int), BigInt::from_f64(float)) {
+			(Some(int), Some(float)) => Some(int.cmp(&float)),
+			_ => Self::float(int)?.partial_cmp(&float),
+		}
+	}
+
+	/// The first unequal pair decides, if one side runs out first it is the smaller one
+	fn compare_items(
+		left: &[Literal],
+		right: &[Literal],
+		visiting: &mut Visiting,
+	) -> Result<Option<Ordering>, RuntimeError> {
+		for (l, r) in left.iter().zip(right) {
+			match Self::compare_within(l, r, visiting)? {
+				Some(Ordering::Equal) => {}
+				ord => return Ok(ord),
+			}
+		}
+		Ok(Some(left.len().cmp(&right.len())))
+	}
+
+	/// Runs `f` unless both sides are the same value or are already being compared further up,
+	/// which happens when they contain themselves. The result is `same` then,
+	/// because any difference between the two is found elsewhere
+	fn visit<T, R>(
+		left: &Rc<T>,
+		right: &Rc<T>,
+		visiting: &mut Visiting,
+		same: R,
+		f: impl FnOnce(&mut Visiting) -> R,
+	) -> R {
+		let pair = (Rc::as_ptr(left).cast(), Rc::as_ptr(right).cast());
+		if Rc::ptr_eq(left, right) || visiting.contains(&pair) {
+			return same;
+		}
+		visiting.push(pair);
+		let result = f(visiting);
+		visiting.pop();
+		result
+	}
+
+	fn comparison<F>(left: Literal, right: Literal, cmp_fn: F) -> Result<Literal, RuntimeError>
+	where
+		F: Fn(Ordering) -> bool,
+	{
+		Ok(match Self::compare(&left, &right)? {
+			Some(ord) if cmp_fn(ord) => True,
+			// Comparisons with NaN are always false
+			_ => False,
+		})
+	}
+
+	/// Integers that overflow continue as big integers up to `MAX_BITS`, as soon as a float
+	/// is involved both sides are promoted to floats
+	fn algebraic<I, B, F, E>(
 		left: Literal,
+		op: &BinaryOp,
 		right: Literal,
-		algebra_fn: F,
+		int_fn: I,
+		big_fn: B,
+		float_fn: F,
 		err: E,
 	) -> Result<Literal, RuntimeError>
 	where
+		I: Fn(i64, i64) -> Option<i64>,
+		B: Fn(&BigInt, &BigInt) -> Option<BigInt>,
 		F: Fn(f64, f64) -> f64,
 		E: Fn(Literal, Literal) -> Result<Literal, RuntimeError>,
 	{
-		if let Number(l) = left {
-			if let Number(r) = right {
-				return Ok(Number(algebra_fn(l, r)));
+		if let (Int(l), Int(r)) = (&left, &right) {
+			if let Some(value) = int_fn(*l, *r) {
+				return Ok(Int(value));
 			}
 		}
+		match (Self::big(&left), Self::big(&right)) {
+			(Some(l), Some(r)) => match big_fn(&l, &r) {
+				Some(value) if !value.is_too_large() => Ok(value.into()),
+				_ => RuntimeError::too_large(&left, op, &right),
+			},
+			_ => match (Self::float(&left), Self::float(&right)) {
+				(Some(l), Some(r)) => Ok(Number(float_fn(l, r))),
+				_ => err(left, right),
+			},
+		}
+	}
+
+	fn big(value: &Literal) -> Option<BigInt> {
+		match value {
+			Int(value) => Some(BigInt::from(*value)),
+			Literal::BigInt(value) => Some((**value).clone()),
+			_ => None,
+		}
+	}
 
-		err(left, right)
+	fn float(value: &Literal) -> Option<f64> {
+		match value {
+			Int(value) => Some(*value as f64),
+			Literal::BigInt(value) => Some(value.to_f64()),
+			Number(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	/// Rounds towards negative infinity, unlike `i64::checked_div` which truncates
+	fn floor_div(left: i64, right: i64) -> Option<i64> {
+		let quotient = left.checked_div(right)?;
+		if left % right != 0 && (left < 0) != (right < 0) {
+			Some(quotient - 1)
+		} else {
+			Some(quotient)
+		}
 	}
 }
@@ -0,0 +1,100 @@
+use super::error::RuntimeError;
+use super::Interpreter;
+use crate::bigint::BigInt;
+use crate::decimal::{Decimal, Rounding};
+use crate::literal::Literal;
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+/// Function implemented in Rust that is available everywhere
+pub struct Native {
+	pub name: &'static str,
+	pub arity: usize,
+	pub fun: fn(&[Literal]) -> Result<Literal, RuntimeError>,
+}
+
+pub const NATIVES: &[Native] = &[
+	Native {
+		name: "compare",
+		arity: 2,
+		fun: compare,
+	},
+	Native {
+		name: "compare_ignore_case",
+		arity: 2,
+		fun: compare_ignore_case,
+	},
+	Native {
+		name: "round",
+		arity: 3,
+		fun: round,
+	},
+];
+
+/// `-1`, `0` or `1` depending on whether the first argument is smaller, equal or greater
+fn compare(args: &[Literal]) -> Result<Literal, RuntimeError> {
+	match Interpreter::compare(&args[0], &args[1])? {
+		Some(ord) => Ok(Literal::Int(ord as i64)),
+		None => RuntimeError::unordered(args[0].clone(), args[1].clone()),
+	}
+}
+
+/// Like `compare` for strings, but upper and lower case letters are treated the same
+/// regardless of the locale
+fn compare_ignore_case(args: &[Literal]) -> Result<Literal, RuntimeError> {
+	let fold = |value: &Literal| match value {
+		Literal::String(value) => Ok(
+			value
+				.chars()
+				.flat_map(char::to_lowercase)
+				.collect::<String>(),
+		),
+		value => RuntimeError::argument("compare_ignore_case", "string", value.clone()),
+	};
+	let ord: Ordering = fold(&args[0])?.cmp(&fold(&args[1])?);
+	Ok(Literal::Int(ord as i64))
+}
+
+/// Rounds a decimal to a number of digits after the decimal point, integers become decimals.
+/// The mode is one of `"half_even"`, `"half_up"`, `"half_down"`, `"up"`, `"down"`, `"floor"` or `"ceiling"`
+fn round(args: &[Literal]) -> Result<Literal, RuntimeError> {
+	let value = match &args[0] {
+		Literal::Decimal(value) => (**value).clone(),
+		Literal::Int(value) => Decimal::from(BigInt::from(*value)),
+		Literal::BigInt(value) => Decimal::from((**value).clone()),
+		value => return RuntimeError::argument("round", "decimal", value.clone()),
+	};
+	let places = match args[1] {
+		Literal::Int(places) => places,
+		ref places => return RuntimeError::argument("round", "int", places.clone()),
+	};
+	let mode = match &args[2] {
+		Literal::String(mode) => match mode.parse::<Rounding>() {
+			Ok(mode) => mode,
+			Err(()) => return RuntimeError::rounding(mode),
+		},
+		mode => return RuntimeError::argument("round", "string", mode.clone()),
+	};
+
+	match u32::try_from(places)
+		.ok()
+		.and_then(|places| value.round(places, mode))
+	{
+		Some(value) => Ok(Literal::Decimal(Rc::new(value))),
+		None => RuntimeError::places(places),
+	}
+}
+
+/// Natives are unique, so their names are enough to tell them apart
+impl PartialEq for Native {
+	fn eq(&self, other: &Self) -> bool {
+		self.name == other.name
+	}
+}
+
+impl Debug for Native {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "<native fun {}>", self.name)
+	}
+}
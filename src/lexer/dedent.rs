@@ -0,0 +1,36 @@
+/// Removes the first and last line break and the indentation all non-blank lines share.
+///
+/// Lines have to use the same indentation characters, tabs and spaces are not mixed up
+pub(crate) fn dedent(text: &str) -> String {
+	let text = text
+		.strip_prefix("\r\n")
+		.or_else(|| text.strip_prefix('\n'))
+		.unwrap_or(text);
+	// The closing quotes are usually on their own, indented line
+	let text = match text.rfind('\n') {
+		Some(index) if text[index + 1..].chars().all(|c| c == ' ' || c == '\t') => {
+			text[..index].strip_suffix('\r').unwrap_or(&text[..index])
+		}
+		_ => text,
+	};
+
+	let indentation = text
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.map(|line| &line[..line.len() - line.trim_start_matches([' ', '\t']).len()])
+		.reduce(|common, indentation| {
+			let len = common
+				.chars()
+				.zip(indentation.chars())
+				.take_while(|(a, b)| a == b)
+				.count();
+			&common[..len]
+		})
+		.unwrap_or("");
+
+	text
+		.split('\n')
+		.map(|line| line.strip_prefix(indentation).unwrap_or(line.trim_start()))
+		.collect::<Vec<_>>()
+		.join("\n")
+}
@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 #[derive(Debug)]
 pub struct LexerError {
 	pub msg: String,
@@ -10,3 +12,9 @@ impl LexerError {
 		Self { msg, line }
 	}
 }
+
+impl Display for LexerError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Line {}: {}", self.line, self.msg)
+	}
+}
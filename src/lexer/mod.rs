@@ -1,17 +1,26 @@
+use crate::bigint::BigInt;
+use crate::decimal::Decimal;
 use crate::literal::Literal;
 use crate::token::{Token, TokenType};
+use std::rc::Rc;
 
+mod dedent;
 mod error;
 mod unescape;
+use dedent::dedent;
 use error::LexerError;
 use unescape::unescape;
 
-const KEYWORDS: [(&str, TokenType); 17] = [
+const KEYWORDS: [(&str, TokenType); 24] = [
 	("and", TokenType::And),
 	("struct", TokenType::Struct),
+	("enum", TokenType::Enum),
+	("impl", TokenType::Impl),
 	("else", TokenType::Else),
 	("false", TokenType::False),
 	("for", TokenType::For),
+	("in", TokenType::In),
+	("step", TokenType::Step),
 	("fun", TokenType::Fun),
 	("if", TokenType::If),
 	("or", TokenType::Or),
@@ -22,6 +31,9 @@ const KEYWORDS: [(&str, TokenType); 17] = [
 	("true", TokenType::True),
 	("while", TokenType::While),
 	("loop", TokenType::Loop),
+	("match", TokenType::Match),
+	("break", TokenType::Break),
+	("continue", TokenType::Continue),
 	("const", TokenType::Const),
 	("mut", TokenType::Mut),
 ];
@@ -34,6 +46,10 @@ pub struct Lexer {
 	line: usize,
 	start: usize,
 	current: usize,
+	/// Open string interpolations, each one counts the `{` nested inside of it
+	interpolations: Vec<usize>,
+	/// Turns float literals into decimals, set by `--decimal`
+	decimals: bool,
 }
 
 impl Lexer {
@@ -45,11 +61,19 @@ impl Lexer {
 			line: 1,
 			start: 0,
 			current: 0,
+			interpolations: Vec::new(),
+			decimals: false,
 		}
 	}
 
+	pub fn with_decimals(mut self, decimals: bool) -> Self {
+		self.decimals = decimals;
+		self
+	}
+
 	pub fn scan(&mut self, input: String) -> Result<&[Token], &[LexerError]> {
 		self.errors.clear();
+		self.interpolations.clear();
 		self.line = 1;
 		self.source = input.chars().collect();
 
@@ -60,6 +84,13 @@ impl Lexer {
 			}
 		}
 
+		if !self.interpolations.is_empty() {
+			self.errors.push(LexerError::new(
+				"Unterminated string interpolation, expected `}`".into(),
+				self.line,
+			));
+		}
+
 		self.tokens.push(Token {
 			typ: TokenType::Eof,
 			lexeme: String::new(),
@@ -86,20 +117,65 @@ impl Lexer {
 			';' => self.push_token(TokenType::Semicolon, None),
 			'(' => self.push_token(TokenType::LeftParen, None),
 			')' => self.push_token(TokenType::RightParen, None),
-			'{' => self.push_token(TokenType::LeftBrace, None),
-			'}' => self.push_token(TokenType::RightBrace, None),
+			'{' => {
+				if let Some(depth) = self.interpolations.last_mut() {
+					*depth += 1;
+				}
+				self.push_token(TokenType::LeftBrace, None)
+			}
+			// Closes the interpolation, the rest of the string follows
+			'}' if self.interpolations.last() == Some(&0) => {
+				self.interpolations.pop();
+				self.string(false);
+			}
+			'}' => {
+				if let Some(depth) = self.interpolations.last_mut() {
+					*depth -= 1;
+				}
+				self.push_token(TokenType::RightBrace, None)
+			}
+			'[' => self.push_token(TokenType::LeftBracket, None),
+			']' => self.push_token(TokenType::RightBracket, None),
+			'#' if self.matches('{') => {
+				if let Some(depth) = self.interpolations.last_mut() {
+					*depth += 1;
+				}
+				self.push_token(TokenType::HashLeftBrace, None)
+			}
 			',' => self.push_token(TokenType::Comma, None),
+			':' if self.matches(':') => self.push_token(TokenType::ColonColon, None),
+			':' => self.push_token(TokenType::Colon, None),
+			'|' => self.push_token(TokenType::Pipe, None),
+			'&' => self.push_token(TokenType::Ampersand, None),
+			'^' => self.push_token(TokenType::Caret, None),
+			'.' if self.matches('.') => {
+				if self.matches('=') {
+					self.push_token(TokenType::DotDotEqual, None)
+				} else {
+					self.push_token(TokenType::DotDot, None)
+				}
+			}
 			'.' => self.push_token(TokenType::Dot, None),
+			'-' if self.matches('=') => self.push_token(TokenType::MinusEqual, None),
 			'-' => self.push_token(TokenType::Minus, None),
+			'+' if self.matches('=') => self.push_token(TokenType::PlusEqual, None),
 			'+' => self.push_token(TokenType::Plus, None),
+			'*' if self.matches('*') => self.push_token(TokenType::StarStar, None),
+			'*' if self.matches('=') => self.push_token(TokenType::StarEqual, None),
 			'*' => self.push_token(TokenType::Star, None),
+			'%' => self.push_token(TokenType::Percent, None),
+			'~' if self.matches('/') => self.push_token(TokenType::TildeSlash, None),
+			'~' => self.push_token(TokenType::Tilde, None),
 			'!' if self.matches('=') => self.push_token(TokenType::BangEqual, None),
 			'!' => self.push_token(TokenType::Bang, None),
 			'=' if self.matches('=') => self.push_token(TokenType::EqualEqual, None),
+			'=' if self.matches('>') => self.push_token(TokenType::FatArrow, None),
 			'=' => self.push_token(TokenType::Equal, None),
 			'<' if self.matches('=') => self.push_token(TokenType::LessEqual, None),
+			'<' if self.matches('<') => self.push_token(TokenType::LessLess, None),
 			'<' => self.push_token(TokenType::Less, None),
 			'>' if self.matches('=') => self.push_token(TokenType::GreaterEqual, None),
+			'>' if self.matches('>') => self.push_token(TokenType::GreaterGreater, None),
 			'>' => self.push_token(TokenType::Greater, None),
 			'/' => {
 				if self.matches('/') {
@@ -127,13 +203,23 @@ impl Lexer {
 						self.advance();
 						self.advance();
 					}
+				} else if self.matches('=') {
+					self.push_token(TokenType::SlashEqual, None);
 				} else {
 					self.push_token(TokenType::Slash, None);
 				}
 			}
-			'"' => self.string(),
+			'"' if self.peek() == '"' && self.peek_next() == '"' => {
+				// Consume the other two "
+				self.advance();
+				self.advance();
+				self.block_string();
+			}
+			'"' => self.string(true),
+			'r' if self.check_raw_string() => self.raw_string(),
+			'\'' => self.char_literal(),
 			'0'..='9' => self.number(),
-			c if c.is_alphabetic() => self.identifier(),
+			c if c.is_alphabetic() || c == '_' => self.identifier(),
 			c => {
 				return Err(LexerError::new(
 					format!("Unexpected character `{c}`"),
@@ -177,59 +263,310 @@ impl Lexer {
 		})
 	}
 
-	fn string(&mut self) {
+	/// Scans text up to the closing `"` or the `{` of the next interpolation,
+	/// `{{` and `}}` are a literal `{` and `}`.
+	///
+	/// `"a {b} c"` becomes `StringHead`, the tokens of `b` and `StringTail`,
+	/// strings without interpolations are a single `String` token
+	fn string(&mut self, head: bool) {
 		let line_at_start = self.line;
+		let mut text = Vec::new();
 
-		while self.peek() != '"' && !self.is_at_end() {
-			if self.peek() == '\n' {
-				self.line += 1
+		let typ = loop {
+			if self.is_at_end() {
+				self.errors.push(LexerError::new(
+					"Unterminated string!".into(),
+					line_at_start,
+				));
+				return;
 			}
-			self.advance();
+			match self.advance() {
+				'"' if head => break TokenType::String,
+				'"' => break TokenType::StringTail,
+				// Kept for `unescape`, so that `\"` does not end the string
+				'\\' if !self.is_at_end() => {
+					text.push('\\');
+					let escaped = self.advance();
+					if escaped == '\n' {
+						self.line += 1
+					}
+					text.push(escaped);
+					// The braces of `\u{XXXX}` do not start an interpolation
+					if escaped == 'u' && self.peek() == '{' {
+						while !matches!(self.peek(), '}' | '"' | '\n') && !self.is_at_end() {
+							text.push(self.advance());
+						}
+						if self.peek() == '}' {
+							text.push(self.advance());
+						}
+					}
+				}
+				'{' if self.matches('{') => text.push('{'),
+				'}' if self.matches('}') => text.push('}'),
+				'{' => {
+					self.interpolations.push(0);
+					break if head {
+						TokenType::StringHead
+					} else {
+						TokenType::StringMiddle
+					};
+				}
+				char => {
+					if char == '\n' {
+						self.line += 1
+					}
+					text.push(char)
+				}
+			}
+		};
+
+		match unescape(&text) {
+			Ok(value) => self.push_token(typ, Some(Literal::String(value))),
+			Err(msg) => self.errors.push(LexerError::new(msg, line_at_start)),
 		}
+	}
 
-		if self.is_at_end() {
+	/// `'a'`, `'\n'` or `'\u{1F600}'`, has to be exactly one character after unescaping
+	fn char_literal(&mut self) {
+		let mut text = Vec::new();
+		while !matches!(self.peek(), '\'' | '\n') && !self.is_at_end() {
+			let char = self.advance();
+			text.push(char);
+			// Kept for `unescape`, so that `\'` does not end the literal
+			if char == '\\' && !self.is_at_end() {
+				text.push(self.advance());
+			}
+		}
+		if !self.matches('\'') {
 			self.errors.push(LexerError::new(
-				"Unterminated string!".into(),
-				line_at_start,
+				"Unterminated char literal!".into(),
+				self.line,
 			));
 			return;
 		}
 
-		// Consume closing "
+		match unescape(&text) {
+			Ok(value) => {
+				let mut chars = value.chars();
+				match (chars.next(), chars.next()) {
+					(Some(char), None) => self.push_token(TokenType::Char, Some(Literal::Char(char))),
+					_ => self.errors.push(LexerError::new(
+						format!("Char literal `'{value}'` has to contain exactly one character"),
+						self.line,
+					)),
+				}
+			}
+			Err(msg) => self.errors.push(LexerError::new(msg, self.line)),
+		}
+	}
+
+	/// `r"` or `r#"` with any number of `#`
+	fn check_raw_string(&self) -> bool {
+		let hashes = self.source[self.current..]
+			.iter()
+			.take_while(|c| **c == '#')
+			.count();
+		self.source.get(self.current + hashes) == Some(&'"')
+	}
+
+	/// `r"..."` and `r#"..."#` are taken as they are, without escapes or interpolations.
+	/// The string ends at the first `"` followed by as many `#` as there were at the start
+	fn raw_string(&mut self) {
+		let line_at_start = self.line;
+		let mut closing = vec!['"'];
+		while self.matches('#') {
+			closing.push('#');
+		}
+		// Consume opening "
 		self.advance();
 
-		let slice = &self.source[self.start + 1..self.current - 1];
-		let value = unescape(slice);
-		self.push_token(TokenType::String, Some(Literal::String(value)));
+		let mut text = String::new();
+		while !self.source[self.current..].starts_with(&closing) {
+			if self.is_at_end() {
+				self.errors.push(LexerError::new(
+					"Unterminated raw string!".into(),
+					line_at_start,
+				));
+				return;
+			}
+			let char = self.advance();
+			if char == '\n' {
+				self.line += 1;
+			}
+			text.push(char);
+		}
+		// Consume closing " and #
+		self.current += closing.len();
+
+		self.push_token(TokenType::String, Some(Literal::String(text)));
 	}
 
+	/// `"""` strings lose their first and last line break and their common indentation,
+	/// escapes work like in normal strings but `{` does not start an interpolation
+	fn block_string(&mut self) {
+		let line_at_start = self.line;
+		let mut text = String::new();
+
+		while !self.source[self.current..].starts_with(&['"', '"', '"']) {
+			if self.is_at_end() {
+				self.errors.push(LexerError::new(
+					"Unterminated block string!".into(),
+					line_at_start,
+				));
+				return;
+			}
+			let char = self.advance();
+			if char == '\n' {
+				self.line += 1;
+			}
+			text.push(char);
+			// Kept for `unescape`, so that `\"` does not end the string
+			if char == '\\' && !self.is_at_end() {
+				let escaped = self.advance();
+				if escaped == '\n' {
+					self.line += 1;
+				}
+				text.push(escaped);
+			}
+		}
+		// Consume closing """
+		self.current += 3;
+
+		let text: Vec<char> = dedent(&text).chars().collect();
+		match unescape(&text) {
+			Ok(value) => self.push_token(TokenType::String, Some(Literal::String(value))),
+			Err(msg) => self.errors.push(LexerError::new(msg, line_at_start)),
+		}
+	}
+
+	/// Base 10 numbers like `1_000`, `0.5` or `6.02e23` and integers with a `0x`, `0o` or `0b` prefix.
+	/// Literals with a fraction or an exponent are floats, all others are integers.
+	/// A `d` suffix makes any of them an exact decimal, `--decimal` does the same for all floats
 	fn number(&mut self) {
-		while self.peek().is_ascii_digit() && !self.is_at_end() {
-			self.advance();
+		let result = match (self.source[self.start], self.peek()) {
+			('0', 'x') => self.radix_number(16, "Hexadecimal"),
+			('0', 'o') => self.radix_number(8, "Octal"),
+			('0', 'b') => self.radix_number(2, "Binary"),
+			_ => self.decimal_number(),
+		};
+
+		match result {
+			Ok(literal) => self.push_token(TokenType::Number, Some(literal)),
+			Err(msg) => self.errors.push(LexerError::new(msg, self.line)),
 		}
+	}
+
+	fn decimal_number(&mut self) -> Result<Literal, String> {
+		self.consume_digits(10);
+		let mut float = false;
 
 		if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+			float = true;
 			// Consume .
 			self.advance();
+			self.consume_digits(10);
+		}
 
-			while self.peek().is_ascii_digit() {
-				self.advance();
+		if matches!(self.peek(), 'e' | 'E') {
+			float = true;
+			// Consume e
+			self.advance();
+			if !self.matches('+') {
+				self.matches('-');
+			}
+			if !self.peek().is_ascii_digit() {
+				return Err(format!(
+					"Exponent of number literal `{}` has no digits",
+					self.lexeme()
+				));
 			}
+			self.consume_digits(10);
 		}
 
-		let value: String = self.source[self.start..self.current]
-			.iter()
-			.map(|c| c.to_string())
-			.collect();
+		// `1.10d` is an exact decimal
+		let decimal = self.matches('d');
+
+		if let Some(suffix) = self.number_suffix() {
+			return Err(format!(
+				"Invalid suffix `{suffix}` on number literal `{}`",
+				self.lexeme()
+			));
+		}
+		let value = self.digits_without_separators(0, 10)?;
 
-		match value.parse::<f64>() {
-			Ok(number) => self.push_token(TokenType::Number, Some(Literal::Number(number))),
-			Err(err) => self
-				.errors
-				.push(LexerError::new(format!("{:?}", err), self.line)),
+		if decimal || (float && self.decimals) {
+			match Decimal::parse(value.trim_end_matches('d')) {
+				Some(value) => Ok(Literal::Decimal(Rc::new(value))),
+				None => Err(format!(
+					"Decimal literal `{}` has too many digits",
+					self.lexeme()
+				)),
+			}
+		} else if float {
+			value
+				.parse::<f64>()
+				.map(Literal::Number)
+				.map_err(|_| format!("Invalid float literal `{}`", self.lexeme()))
+		} else {
+			Ok(BigInt::parse(&value, 10).into())
 		}
 	}
 
+	/// `kind` is used in error messages, like "Hexadecimal"
+	fn radix_number(&mut self, radix: u32, kind: &str) -> Result<Literal, String> {
+		// Consume the x, o or b of the prefix
+		self.advance();
+		self.consume_digits(radix);
+		if let Some(suffix) = self.number_suffix() {
+			return Err(format!(
+				"Invalid digit `{suffix}` in {} literal `{}`",
+				kind.to_lowercase(),
+				self.lexeme()
+			));
+		}
+
+		let digits = self.digits_without_separators(2, radix)?;
+		if digits.is_empty() {
+			return Err(format!("{kind} literal `{}` has no digits", self.lexeme()));
+		}
+		Ok(BigInt::parse(&digits, radix).into())
+	}
+
+	fn consume_digits(&mut self, radix: u32) {
+		while self.peek().is_digit(radix) || self.peek() == '_' {
+			self.advance();
+		}
+	}
+
+	/// Consumes letters and digits that directly follow a number, they are always an error
+	fn number_suffix(&mut self) -> Option<String> {
+		let start = self.current;
+		while self.peek().is_alphanumeric() || self.peek() == '_' {
+			self.advance();
+		}
+		(start != self.current).then(|| self.source[start..self.current].iter().collect())
+	}
+
+	/// Removes the `_` separators of the current literal, skipping the first `skip` characters.
+	/// A separator has to be placed between two digits, only directly after a prefix is it allowed at the start
+	fn digits_without_separators(&self, skip: usize, radix: u32) -> Result<String, String> {
+		let chars = &self.source[self.start + skip..self.current];
+		let is_digit = |index: usize| chars.get(index).is_some_and(|c| c.is_digit(radix));
+		let misplaced = (chars.iter().enumerate())
+			.any(|(i, c)| *c == '_' && (!is_digit(i + 1) || (i > 0 && !is_digit(i - 1))));
+		if misplaced {
+			return Err(format!(
+				"Misplaced `_` in number literal `{}`, it can only separate digits",
+				self.lexeme()
+			));
+		}
+		Ok(chars.iter().filter(|c| **c != '_').collect())
+	}
+
+	fn lexeme(&self) -> String {
+		self.source[self.start..self.current].iter().collect()
+	}
+
 	fn advance(&mut self) -> char {
 		self.current += 1;
 		self.source[self.current - 1]
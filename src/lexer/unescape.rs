@@ -1,24 +1,66 @@
-pub(crate) fn unescape(slice: &[char]) -> String {
+/// Replaces escape sequences with the characters they stand for,
+/// the error describes the first invalid escape
+pub(crate) fn unescape(slice: &[char]) -> Result<String, String> {
 	let mut iter = slice.iter().peekable();
 	let mut result = String::new();
 	while let Some(c) = iter.next() {
-		// TODO: Implement other escape codes
-		// https://doc.rust-lang.org/stable/nightly-rustc/src/rustc_lexer/unescape.rs.html
-		if *c == '\\' {
-			match iter.peek() {
-				Some('t') => {
-					iter.next();
-					result.push('\t');
+		if *c != '\\' {
+			result.push(*c);
+			continue;
+		}
+		let escaped = match iter.next() {
+			Some('t') => '\t',
+			Some('n') => '\n',
+			Some('r') => '\r',
+			Some('0') => '\0',
+			Some('\\') => '\\',
+			Some('"') => '"',
+			Some('\'') => '\'',
+			Some('x') => {
+				let digits: String = (0..2).filter_map(|_| iter.next()).collect();
+				let value = match u8::from_str_radix(&digits, 16) {
+					Ok(value) if digits.len() == 2 && digits.chars().all(|c| c.is_ascii_hexdigit()) => value,
+					_ => return Err(format!("Escape `\\x{digits}` needs exactly two hex digits")),
+				};
+				if value > 0x7f {
+					return Err(format!(
+						"Escape `\\x{digits}` is out of range, only `\\x00` to `\\x7F` are allowed"
+					));
+				}
+				value as char
+			}
+			Some('u') => {
+				if iter.next() != Some(&'{') {
+					return Err("Unicode escape has to look like `\\u{XXXX}`".into());
 				}
-				Some('n') => {
-					iter.next();
-					result.push('\n');
+				let mut digits = String::new();
+				loop {
+					match iter.next() {
+						Some('}') => break,
+						Some(c) if c.is_ascii_hexdigit() && digits.len() < 6 => digits.push(*c),
+						_ => {
+							return Err(format!(
+								"Unicode escape `\\u{{{digits}` needs one to six hex digits and a closing `}}`"
+							))
+						}
+					}
+				}
+				match u32::from_str_radix(&digits, 16)
+					.ok()
+					.and_then(char::from_u32)
+				{
+					Some(c) => c,
+					None => {
+						return Err(format!(
+							"Unicode escape `\\u{{{digits}}}` is not a valid code point"
+						))
+					}
 				}
-				_ => result.push('\\'),
 			}
-		} else {
-			result.push(*c)
-		}
+			Some(c) => return Err(format!("Unknown escape sequence `\\{c}`")),
+			None => return Err("Unfinished escape sequence".into()),
+		};
+		result.push(escaped);
 	}
-	result
+	Ok(result)
 }
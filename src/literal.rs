@@ -1,31 +1,200 @@
+use crate::bigint::BigInt;
+use crate::decimal::Decimal;
+use crate::interpreter::{Enum, Function, Instance, Map, Native, Struct, Variant};
+use std::cell::RefCell;
 use std::fmt::Display;
+use std::rc::Rc;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
+	/// `()`, the value of everything that does not produce anything else
+	Unit,
 	True,
 	False,
+	Int(i64),
+	/// Only used for integers that do not fit into an `Int`
+	BigInt(Rc<BigInt>),
+	/// Floating point number
 	Number(f64),
+	/// Exact base 10 number, like `1.10d`
+	Decimal(Rc<Decimal>),
 	String(String),
+	Char(char),
+	Range(Range),
+	/// Fixed size and immutable, so copies can share the items
+	Tuple(Rc<Vec<Literal>>),
+	/// Shared between all copies, like instances
+	List(Rc<RefCell<Vec<Literal>>>),
+	Map(Rc<RefCell<Map>>),
+	Function(Rc<Function>),
+	Native(&'static Native),
+	Struct(Rc<Struct>),
+	Instance(Rc<RefCell<Instance>>),
+	Enum(Rc<Enum>),
+	Variant(Rc<Variant>),
+	/// Creates the variant at the index, like `Shape::Circle` in `Shape::Circle(2)`
+	Constructor(Rc<Enum>, usize),
 }
 
 impl Display for Literal {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
+			Literal::Unit => write!(f, "()"),
 			Literal::True => write!(f, "true"),
 			Literal::False => write!(f, "false"),
+			Literal::Int(value) => write!(f, "{value}"),
+			Literal::BigInt(value) => write!(f, "{value}"),
+			// Keep the `.0` so floats can be told apart from integers
+			Literal::Number(value) if value.fract() == 0.0 && value.abs() < 1e16 => {
+				write!(f, "{value:.1}")
+			}
+			// Large floats are rounded, so they must not look like exact integers
+			Literal::Number(value) if value.abs() >= 1e16 => write!(f, "{value:e}"),
 			Literal::Number(value) => write!(f, "{value}"),
+			Literal::Decimal(value) => write!(f, "{value}"),
 			Literal::String(value) => write!(f, "{value}"),
+			Literal::Char(value) => write!(f, "{value}"),
+			Literal::Range(range) => write!(f, "{range}"),
+			Literal::Tuple(items) => {
+				write!(f, "(")?;
+				for (i, item) in items.iter().enumerate() {
+					if i > 0 {
+						write!(f, ", ")?;
+					}
+					write!(f, "{item}")?;
+				}
+				// `(1,)` to tell it apart from a group
+				if items.len() == 1 {
+					write!(f, ",")?;
+				}
+				write!(f, ")")
+			}
+			Literal::List(items) => write_once(f, Rc::as_ptr(items).cast(), "[...]", |f| {
+				write!(f, "[")?;
+				for (i, item) in items.borrow().iter().enumerate() {
+					if i > 0 {
+						write!(f, ", ")?;
+					}
+					write!(f, "{item}")?;
+				}
+				write!(f, "]")
+			}),
+			Literal::Map(map) => write_once(f, Rc::as_ptr(map).cast(), "#{...}", |f| {
+				write!(f, "{}", map.borrow())
+			}),
+			Literal::Function(function) => write!(f, "{function:?}"),
+			Literal::Native(native) => write!(f, "{native:?}"),
+			Literal::Struct(class) => write!(f, "{class:?}"),
+			Literal::Instance(instance) => write_once(
+				f,
+				Rc::as_ptr(instance).cast(),
+				format_args!("{} {{...}}", instance.borrow().class.name),
+				|f| write!(f, "{}", instance.borrow()),
+			),
+			Literal::Enum(class) => write!(f, "{class:?}"),
+			Literal::Variant(variant) => write!(f, "{variant}"),
+			Literal::Constructor(class, index) => {
+				write!(
+					f,
+					"<constructor {}::{}>",
+					class.name, class.variants[*index].0
+				)
+			}
 		}
 	}
 }
 
+thread_local! {
+	/// Addresses of the shared values that are being printed right now
+	static PRINTING: RefCell<Vec<*const ()>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `write` unless the value at `ptr` is already being printed further up,
+/// then it contains itself and `cycle` is written in its place
+fn write_once(
+	f: &mut std::fmt::Formatter<'_>,
+	ptr: *const (),
+	cycle: impl Display,
+	write: impl FnOnce(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+) -> std::fmt::Result {
+	if PRINTING.with(|printing| printing.borrow().contains(&ptr)) {
+		return write!(f, "{cycle}");
+	}
+	PRINTING.with(|printing| printing.borrow_mut().push(ptr));
+	let result = write(f);
+	PRINTING.with(|printing| printing.borrow_mut().pop());
+	result
+}
+
 impl Literal {
 	pub fn to_type_string(&self) -> String {
 		match self {
+			Literal::Unit => "()".into(),
 			Literal::True => "true".into(),
 			Literal::False => "false".into(),
-			Literal::Number(_) => "number".into(),
+			Literal::Int(_) | Literal::BigInt(_) => "int".into(),
+			Literal::Number(_) => "float".into(),
+			Literal::Decimal(_) => "decimal".into(),
 			Literal::String(_) => "string".into(),
+			Literal::Char(_) => "char".into(),
+			Literal::Range(_) => "range".into(),
+			Literal::Tuple(_) => "tuple".into(),
+			Literal::List(_) => "list".into(),
+			Literal::Map(_) => "map".into(),
+			Literal::Function(_) | Literal::Native(_) => "function".into(),
+			Literal::Struct(_) => "struct".into(),
+			Literal::Instance(instance) => instance.borrow().class.name.clone(),
+			Literal::Enum(_) => "enum".into(),
+			Literal::Variant(variant) => variant.class.name.clone(),
+			Literal::Constructor(..) => "function".into(),
+		}
+	}
+}
+
+/// Shrinks the value back into an `Int` if it fits
+impl From<BigInt> for Literal {
+	fn from(value: BigInt) -> Self {
+		match value.to_i64() {
+			Some(value) => Literal::Int(value),
+			None => Literal::BigInt(Rc::new(value)),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+	pub start: i64,
+	pub end: i64,
+	/// Never zero, a negative step counts downwards
+	pub step: i64,
+	pub inclusive: bool,
+}
+
+impl Range {
+	/// Yields `start + n * step` for as long as the value has not passed `end`
+	pub fn iter(self) -> impl Iterator<Item = i64> {
+		std::iter::successors(Some(self.start), move |value| value.checked_add(self.step))
+			.take_while(move |value| self.contains(*value))
+	}
+
+	fn contains(&self, value: i64) -> bool {
+		match (self.step > 0, self.inclusive) {
+			(true, true) => value <= self.end,
+			(true, false) => value < self.end,
+			(false, true) => value >= self.end,
+			(false, false) => value > self.end,
+		}
+	}
+}
+
+impl Display for Range {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.start)?;
+		write!(f, "{}", if self.inclusive { "..=" } else { ".." })?;
+		write!(f, "{}", self.end)?;
+		if self.step != 1 {
+			write!(f, " step {}", self.step)?;
 		}
+		Ok(())
 	}
 }
@@ -1,7 +1,10 @@
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 use std::path::Path;
+use std::thread;
 
+mod bigint;
+mod decimal;
 mod expr;
 mod interpreter;
 mod lexer;
@@ -13,10 +16,15 @@ use lexer::Lexer;
 use parser::Parser;
 use token::TokenType;
 
-fn run(input: String) {
-	let mut lexer = Lexer::new();
+/// Every nested call and expression of a script uses the native stack,
+/// which the main thread does not have enough of for deep recursion
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// `decimal` makes all float literals and quotients of integers exact decimals
+fn run(input: String, decimal: bool) {
+	let mut lexer = Lexer::new().with_decimals(decimal);
 	let mut parser = Parser::new();
-	let mut interpreter = Interpreter::new();
+	let mut interpreter = Interpreter::new().with_decimals(decimal);
 
 	match lexer.scan(input) {
 		Ok(tokens) => {
@@ -31,28 +39,32 @@ fn run(input: String) {
 			match parser.parse(tokens.to_vec()) {
 				Ok(ast) => {
 					println!("{:#?}", ast);
+					parser
+						.warnings()
+						.iter()
+						.for_each(|warning| println!("{warning}"));
 
 					println!("\n--- Interpreter ---");
 					if let Err(err) = interpreter.eval(ast) {
-						println!("Runtime Error: {:?}", err)
+						println!("Runtime Error: {err}")
 					}
 				}
 				Err(errors) => errors.iter().for_each(|err| println!("{err}")),
 			}
 			println!()
 		}
-		Err(errors) => errors.iter().for_each(|err| println!("{:?}", err)),
+		Err(errors) => errors.iter().for_each(|err| println!("{err}")),
 	}
 
 	println!()
 }
 
-fn run_file<P: AsRef<Path>>(path: P) -> Result<(), std::io::Error> {
-	run(std::fs::read_to_string(path)?);
+fn run_file<P: AsRef<Path>>(path: P, decimal: bool) -> Result<(), std::io::Error> {
+	run(std::fs::read_to_string(path)?, decimal);
 	Ok(())
 }
 
-fn run_repl() {
+fn run_repl(decimal: bool) {
 	let mut rl = Editor::<()>::new();
 	if rl.load_history(".history").is_err() {
 		println!("No previous history.");
@@ -62,7 +74,7 @@ fn run_repl() {
 		match readline {
 			Ok(line) => {
 				rl.add_history_entry(line.as_str());
-				run(line);
+				run(line, decimal);
 			}
 			Err(ReadlineError::Interrupted) => {
 				println!("CTRL-C");
@@ -82,15 +94,29 @@ fn run_repl() {
 }
 
 fn main() -> Result<(), std::io::Error> {
-	let mut args = std::env::args().skip(1);
+	let mut decimal = false;
+	let mut path = None;
 
-	match args.next() {
-		Some(value) => match value.as_str() {
-			"help" | "-h" | "--help" => println!("Usage: rys [path to script]"),
-			path => run_file(path)?,
-		},
-		None => run_repl(),
+	for arg in std::env::args().skip(1) {
+		match arg.as_str() {
+			"help" | "-h" | "--help" => {
+				println!("Usage: rys [--decimal] [path to script]");
+				return Ok(());
+			}
+			"--decimal" => decimal = true,
+			_ => path = Some(arg),
+		}
 	}
 
-	Ok(())
+	thread::Builder::new()
+		.stack_size(STACK_SIZE)
+		.spawn(move || match path {
+			Some(path) => run_file(path, decimal),
+			None => {
+				run_repl(decimal);
+				Ok(())
+			}
+		})?
+		.join()
+		.expect("Interpreter thread panicked")
 }
@@ -6,6 +6,7 @@ use crate::token::*;
 pub enum ParseError {
 	TokenMismatch(Token, String),
 	InvalidAssignmentTarget(Token),
+	Misplaced(Token, String),
 }
 
 impl ParseError {
@@ -14,6 +15,23 @@ impl ParseError {
 	}
 }
 
+#[derive(Debug)]
+pub enum ParseWarning {
+	MissingWildcardArm(Token),
+}
+
+impl Display for ParseWarning {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ParseWarning::MissingWildcardArm(token) => write!(
+				f,
+				"Line {}: Warning: `match` has no wildcard arm, unmatched values cause a runtime error",
+				token.line
+			),
+		}
+	}
+}
+
 impl Display for ParseError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -32,6 +50,7 @@ impl Display for ParseError {
 					token.line, token.lexeme
 				)
 			}
+			ParseError::Misplaced(token, msg) => write!(f, "Line {}: {msg}", token.line),
 		}
 	}
 }
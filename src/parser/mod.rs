@@ -1,13 +1,31 @@
-use crate::expr::{BinaryOp, Expr, Stmt, UnaryOp};
+use crate::expr::{
+	AssignTarget, BinaryOp, Block, Expr, FunDecl, LogicalOp, MatchArm, Pattern, Stmt, UnaryOp,
+	VariantDecl,
+};
+use crate::literal::Literal;
 use crate::token::{Token, TokenType};
+use std::collections::HashMap;
+use std::rc::Rc;
 
 mod error;
 use error::ParseError;
+pub use error::ParseWarning;
 
 pub struct Parser {
 	tokens: Vec<Token>,
 	errors: Vec<ParseError>,
+	warnings: Vec<ParseWarning>,
 	current: usize,
+	/// Kinds of the loops surrounding the current position, innermost last
+	loops: Vec<TokenType>,
+	/// Whether the current position is inside of a function body
+	in_function: bool,
+	/// Whether the current position is inside of an `impl` block
+	in_impl: bool,
+	/// Disabled right before a block, where `Name {` has to start the block
+	struct_literals: bool,
+	/// Variant names of the enums declared so far, used to check matches for exhaustiveness
+	enums: HashMap<String, Vec<String>>,
 }
 
 impl Parser {
@@ -15,14 +33,26 @@ impl Parser {
 		Self {
 			tokens: Vec::new(),
 			errors: Vec::new(),
+			warnings: Vec::new(),
 			current: 0,
+			loops: Vec::new(),
+			in_function: false,
+			in_impl: false,
+			struct_literals: true,
+			enums: HashMap::new(),
 		}
 	}
 
 	pub fn parse(&mut self, tokens: Vec<Token>) -> Result<Vec<Stmt>, &[ParseError]> {
 		self.tokens = tokens;
 		self.errors.clear();
+		self.warnings.clear();
 		self.current = 0;
+		self.loops.clear();
+		self.in_function = false;
+		self.in_impl = false;
+		self.struct_literals = true;
+		self.enums.clear();
 
 		let statements = self.program();
 
@@ -32,6 +62,10 @@ impl Parser {
 			Err(&self.errors)
 		}
 	}
+
+	pub fn warnings(&self) -> &[ParseWarning] {
+		&self.warnings
+	}
 }
 
 /// Grammar definition
@@ -53,7 +87,8 @@ impl Parser {
 		}
 	}
 
-	/// declaration => const_decl | mut_decl | statement
+	/// declaration => const_decl | mut_decl | fun_decl | struct_decl | enum_decl | impl_decl
+	///   | statement
 	fn declaration(&mut self) -> Result<Stmt, ParseError> {
 		// "const" part of const_decl
 		if self.matches(TokenType::Const) {
@@ -63,11 +98,157 @@ impl Parser {
 		if self.matches(TokenType::Mut) {
 			return self.mut_decl();
 		}
+		// "fun" part of fun_decl
+		if self.matches(TokenType::Fun) {
+			return Ok(Stmt::Fun(self.fun_decl()?));
+		}
+		// "struct" part of struct_decl
+		if self.matches(TokenType::Struct) {
+			return self.struct_decl();
+		}
+		// "enum" part of enum_decl
+		if self.matches(TokenType::Enum) {
+			return self.enum_decl();
+		}
+		// "impl" part of impl_decl
+		if self.matches(TokenType::Impl) {
+			return self.impl_decl();
+		}
 		self.statement()
 	}
 
-	/// const_decl => "const" IDENTIFIER "=" expression ";"
+	/// struct_decl => "struct" IDENTIFIER (":" IDENTIFIER)?
+	///   "{" (IDENTIFIER ("," IDENTIFIER)* ","?)? "}"
+	fn struct_decl(&mut self) -> Result<Stmt, ParseError> {
+		let name = self
+			.consume(TokenType::Identifier, "Expected struct name")?
+			.lexeme
+			.clone();
+		let parent = if self.matches(TokenType::Colon) {
+			let parent = self.consume(TokenType::Identifier, "Expected parent struct name")?;
+			Some(parent.lexeme.clone())
+		} else {
+			None
+		};
+		self.consume(TokenType::LeftBrace, "Expected `{` after struct name")?;
+		let fields = self.list(TokenType::RightBrace, |this| {
+			Ok(
+				this
+					.consume(TokenType::Identifier, "Expected field name")?
+					.lexeme
+					.clone(),
+			)
+		})?;
+
+		Ok(Stmt::Struct {
+			name,
+			parent,
+			fields,
+		})
+	}
+
+	/// enum_decl => "enum" IDENTIFIER "{" (variant ("," variant)* ","?)? "}"
+	/// variant => IDENTIFIER ("(" IDENTIFIER ("," IDENTIFIER)* ","? ")")?
+	fn enum_decl(&mut self) -> Result<Stmt, ParseError> {
+		let name = self
+			.consume(TokenType::Identifier, "Expected enum name")?
+			.lexeme
+			.clone();
+		self.consume(TokenType::LeftBrace, "Expected `{` after enum name")?;
+		let variants = self.list(TokenType::RightBrace, |this| {
+			let name = this
+				.consume(TokenType::Identifier, "Expected variant name")?
+				.lexeme
+				.clone();
+			let fields = if this.matches(TokenType::LeftParen) {
+				this.list(TokenType::RightParen, |this| {
+					Ok(
+						this
+							.consume(TokenType::Identifier, "Expected field name")?
+							.lexeme
+							.clone(),
+					)
+				})?
+			} else {
+				Vec::new()
+			};
+			Ok(VariantDecl { name, fields })
+		})?;
+
+		self.enums.insert(
+			name.clone(),
+			variants
+				.iter()
+				.map(|variant| variant.name.clone())
+				.collect(),
+		);
+		Ok(Stmt::Enum { name, variants })
+	}
+
+	/// impl_decl => "impl" IDENTIFIER "{" ("fun" fun_decl)* "}"
+	fn impl_decl(&mut self) -> Result<Stmt, ParseError> {
+		let name = self
+			.consume(TokenType::Identifier, "Expected struct name")?
+			.lexeme
+			.clone();
+		self.consume(TokenType::LeftBrace, "Expected `{` after struct name")?;
+
+		let in_impl = std::mem::replace(&mut self.in_impl, true);
+		let mut methods = Vec::new();
+		let result = loop {
+			if self.matches(TokenType::RightBrace) {
+				break Ok(());
+			}
+			if let Err(err) = self.consume(TokenType::Fun, "Expected method") {
+				break Err(err);
+			}
+			match self.fun_decl() {
+				Ok(method) => methods.push(method),
+				Err(err) => break Err(err),
+			}
+		};
+		self.in_impl = in_impl;
+		result?;
+
+		Ok(Stmt::Impl { name, methods })
+	}
+
+	/// fun_decl => "fun" IDENTIFIER "(" parameters? ")" block
+	/// parameters => IDENTIFIER ("," IDENTIFIER)* ","?
+	fn fun_decl(&mut self) -> Result<Rc<FunDecl>, ParseError> {
+		let name = self
+			.consume(TokenType::Identifier, "Expected function name")?
+			.lexeme
+			.clone();
+		self.consume(TokenType::LeftParen, "Expected `(` after function name")?;
+		let params = self.list(TokenType::RightParen, |this| {
+			Ok(
+				this
+					.consume(TokenType::Identifier, "Expected parameter name")?
+					.lexeme
+					.clone(),
+			)
+		})?;
+
+		// Loops outside of the function can not be left from inside of it
+		let loops = std::mem::take(&mut self.loops);
+		let in_function = std::mem::replace(&mut self.in_function, true);
+		let body = self.block();
+		self.loops = loops;
+		self.in_function = in_function;
+
+		Ok(Rc::new(FunDecl {
+			name,
+			params,
+			body: body?,
+		}))
+	}
+
+	/// const_decl => "const" (IDENTIFIER "=" expression ";" | destructure)
 	fn const_decl(&mut self) -> Result<Stmt, ParseError> {
+		if self.matches(TokenType::LeftParen) {
+			return self.destructure(false);
+		}
 		let name = self
 			.consume(TokenType::Identifier, "Expected variable name")?
 			.lexeme
@@ -85,14 +266,21 @@ impl Parser {
 		}
 	}
 
-	/// mut_decl => "mut" IDENTIFIER "=" expression ";"
+	/// mut_decl => "mut" (IDENTIFIER ("=" expression)? ";" | destructure)
 	fn mut_decl(&mut self) -> Result<Stmt, ParseError> {
+		if self.matches(TokenType::LeftParen) {
+			return self.destructure(true);
+		}
 		let name = self
 			.consume(TokenType::Identifier, "Expected variable name")?
 			.lexeme
 			.clone();
-		self.consume(TokenType::Equal, "Expected `=`")?;
-		let initializer = self.expression()?;
+		// Uninitialized variables start out as `()`
+		let initializer = if self.matches(TokenType::Equal) {
+			self.expression()?
+		} else {
+			Expr::Literal(Literal::Unit)
+		};
 		let next = self.advance();
 		match next.typ {
 			TokenType::Semicolon | TokenType::Eof => Ok(Stmt::Var {
@@ -104,37 +292,311 @@ impl Parser {
 		}
 	}
 
-	/// statement => exprStmt | printStmt
+	/// destructure => "(" (IDENTIFIER ("," IDENTIFIER)* ","?)? ")" "=" expression ";"
+	///
+	/// `_` skips a value of the tuple
+	fn destructure(&mut self, mutable: bool) -> Result<Stmt, ParseError> {
+		let names = self.list(TokenType::RightParen, |this| {
+			let name = this
+				.consume(TokenType::Identifier, "Expected variable name or `_`")?
+				.lexeme
+				.clone();
+			Ok((name != "_").then_some(name))
+		})?;
+		self.consume(TokenType::Equal, "Expected `=`")?;
+		let initializer = self.expression()?;
+		let next = self.advance();
+		match next.typ {
+			TokenType::Semicolon | TokenType::Eof => Ok(Stmt::Destructure {
+				names,
+				initializer,
+				mutable,
+			}),
+			_ => ParseError::token_mismatch(next, "Expected `;`"),
+		}
+	}
+
+	/// statement => exprStmt | printStmt | block_like_stmt
 	fn statement(&mut self) -> Result<Stmt, ParseError> {
 		// print_stmt => "print" expression (";" | EOF)
 		if self.matches(TokenType::Print) {
 			return self.print_stmt();
 		}
-		// block "{" declaration* "}"
-		if self.matches(TokenType::LeftBrace) {
-			return Ok(Stmt::Block(self.block()?));
+		// block_like_stmt => block_like ";"?
+		if self.check_block_like() {
+			let expr = self.block_like()?;
+			self.matches(TokenType::Semicolon);
+			return Ok(Stmt::Expr(expr));
 		}
 		self.expr_stmt()
 	}
 
-	/// block "{" declaration* "}"
-	fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
-		let mut statements = Vec::new();
+	/// block => "{" declaration* expression? "}"
+	fn block(&mut self) -> Result<Block, ParseError> {
+		self.consume(TokenType::LeftBrace, "Expected `{`")?;
+		self.with_struct_literals(true, Self::block_body)
+	}
+
+	fn block_body(&mut self) -> Result<Block, ParseError> {
+		let mut stmts = Vec::new();
 		loop {
-			if self.check(TokenType::RightBrace) || self.is_at_end() {
-				self.advance();
-				break Ok(statements);
+			if self.matches(TokenType::RightBrace) {
+				break Ok(Block { stmts, value: None });
+			}
+			if self.is_at_end() {
+				break ParseError::token_mismatch(self.peek(), "Expected closing `}`");
 			}
 			while self.matches(TokenType::NewLine) {
 				self.advance();
 			}
 			match self.declaration() {
-				Ok(stmt) => statements.push(stmt),
+				// An expression that is not terminated by `;` becomes the value of the block
+				Ok(Stmt::Expr(expr))
+					if self.check(TokenType::RightBrace) && self.previous().typ != TokenType::Semicolon =>
+				{
+					self.advance();
+					break Ok(Block {
+						stmts,
+						value: Some(Box::new(expr)),
+					});
+				}
+				Ok(stmt) => stmts.push(stmt),
 				Err(err) => self.errors.push(err),
 			}
 		}
 	}
 
+	/// block_like => block | if_expr | while_expr | loop_expr | for_expr | match_expr
+	fn block_like(&mut self) -> Result<Expr, ParseError> {
+		if self.matches(TokenType::If) {
+			return self.if_expr();
+		}
+		if self.matches(TokenType::While) {
+			return self.while_expr();
+		}
+		if self.matches(TokenType::Loop) {
+			return self.loop_expr();
+		}
+		if self.matches(TokenType::For) {
+			return self.for_expr();
+		}
+		if self.matches(TokenType::Match) {
+			return self.match_expr();
+		}
+		Ok(Expr::Block(self.block()?))
+	}
+
+	/// if_expr => "if" expression block ("else" (if_expr | block))?
+	fn if_expr(&mut self) -> Result<Expr, ParseError> {
+		let condition = Box::new(self.condition()?);
+		let then_branch = self.block()?;
+
+		let else_branch = if self.matches(TokenType::Else) {
+			if self.matches(TokenType::If) {
+				Some(Box::new(self.if_expr()?))
+			} else {
+				Some(Box::new(Expr::Block(self.block()?)))
+			}
+		} else {
+			None
+		};
+
+		Ok(Expr::If {
+			condition,
+			then_branch,
+			else_branch,
+		})
+	}
+
+	/// while_expr => "while" expression block
+	fn while_expr(&mut self) -> Result<Expr, ParseError> {
+		let condition = Box::new(self.condition()?);
+		let body = self.loop_body(TokenType::While)?;
+		Ok(Expr::While { condition, body })
+	}
+
+	/// loop_expr => "loop" block
+	fn loop_expr(&mut self) -> Result<Expr, ParseError> {
+		Ok(Expr::Loop(self.loop_body(TokenType::Loop)?))
+	}
+
+	/// for_expr => "for" IDENTIFIER "in" expression block
+	fn for_expr(&mut self) -> Result<Expr, ParseError> {
+		let name = self
+			.consume(TokenType::Identifier, "Expected loop variable name")?
+			.lexeme
+			.clone();
+		self.consume(TokenType::In, "Expected `in`")?;
+		let iterable = Box::new(self.condition()?);
+		let body = self.loop_body(TokenType::For)?;
+		Ok(Expr::For {
+			name,
+			iterable,
+			body,
+		})
+	}
+
+	/// match_expr => "match" expression "{" (match_arm ","?)* "}"
+	/// match_arm => pattern ("if" expression)? "=>" expression
+	fn match_expr(&mut self) -> Result<Expr, ParseError> {
+		let keyword = self.previous().clone();
+		let value = Box::new(self.condition()?);
+		self.consume(TokenType::LeftBrace, "Expected `{` after match value")?;
+
+		let mut arms = Vec::new();
+		while !self.matches(TokenType::RightBrace) {
+			let pattern = self.pattern()?;
+			let guard = if self.matches(TokenType::If) {
+				Some(self.expression()?)
+			} else {
+				None
+			};
+			self.consume(TokenType::FatArrow, "Expected `=>`")?;
+
+			// Arms with a block as body do not need to be separated by `,`
+			let block_like = self.check_block_like();
+			let body = self.expression()?;
+			if !self.matches(TokenType::Comma) && !block_like && !self.check(TokenType::RightBrace) {
+				return ParseError::token_mismatch(self.peek(), "Expected `,` or `}` after match arm");
+			}
+
+			arms.push(MatchArm {
+				pattern,
+				guard,
+				body,
+			});
+		}
+
+		if !self.is_exhaustive(&arms) {
+			self
+				.warnings
+				.push(ParseWarning::MissingWildcardArm(keyword));
+		}
+
+		Ok(Expr::Match { value, arms })
+	}
+
+	/// Arms are exhaustive if one of them matches anything,
+	/// or if they cover every variant of an enum declared earlier
+	fn is_exhaustive(&self, arms: &[MatchArm]) -> bool {
+		let mut variants: Vec<(&String, &String)> = Vec::new();
+		for arm in arms.iter().filter(|arm| arm.guard.is_none()) {
+			if arm.pattern.is_catch_all() {
+				return true;
+			}
+			let patterns = match &arm.pattern {
+				Pattern::Or(patterns) => patterns.iter().collect(),
+				pattern => vec![pattern],
+			};
+			for pattern in patterns {
+				if let Pattern::Variant {
+					path: (class, name),
+					values,
+				} = pattern
+				{
+					if values.iter().all(Pattern::is_catch_all) {
+						variants.push((class, name));
+					}
+				}
+			}
+		}
+
+		match variants.first() {
+			Some((class, _)) => match self.enums.get(*class) {
+				Some(names) => names.iter().all(|name| variants.contains(&(class, name))),
+				None => false,
+			},
+			None => false,
+		}
+	}
+
+	/// pattern => single_pattern ("|" single_pattern)*
+	fn pattern(&mut self) -> Result<Pattern, ParseError> {
+		let mut patterns = vec![self.single_pattern()?];
+		while self.matches(TokenType::Pipe) {
+			patterns.push(self.single_pattern()?);
+		}
+
+		if patterns.len() == 1 {
+			Ok(patterns.remove(0))
+		} else {
+			Ok(Pattern::Or(patterns))
+		}
+	}
+
+	/// single_pattern => "_" | IDENTIFIER | variant_pattern
+	///   | pattern_literal ((".." | "..=") pattern_literal)?
+	/// variant_pattern => IDENTIFIER "::" IDENTIFIER ("(" (pattern ("," pattern)* ","?)? ")")?
+	fn single_pattern(&mut self) -> Result<Pattern, ParseError> {
+		if self.matches(TokenType::Identifier) {
+			let name = self.previous().lexeme.clone();
+			if self.matches(TokenType::ColonColon) {
+				let variant = self
+					.consume(TokenType::Identifier, "Expected variant name after `::`")?
+					.lexeme
+					.clone();
+				let values = if self.matches(TokenType::LeftParen) {
+					self.list(TokenType::RightParen, Self::pattern)?
+				} else {
+					Vec::new()
+				};
+				return Ok(Pattern::Variant {
+					path: (name, variant),
+					values,
+				});
+			}
+			return Ok(if name == "_" {
+				Pattern::Wildcard
+			} else {
+				Pattern::Binding(name)
+			});
+		}
+
+		let start = self.pattern_literal()?;
+		if self.matches_any(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+			let inclusive = self.previous().typ == TokenType::DotDotEqual;
+			let end = self.pattern_literal()?;
+			return Ok(Pattern::Range {
+				start,
+				end,
+				inclusive,
+			});
+		}
+
+		Ok(Pattern::Literal(start))
+	}
+
+	/// pattern_literal => "-"? NUMBER | STRING | "true" | "false"
+	fn pattern_literal(&mut self) -> Result<Literal, ParseError> {
+		let negative = self.matches(TokenType::Minus);
+		let token = self.advance().clone();
+
+		match (negative, token.typ, token.literal) {
+			(true, TokenType::Number, Some(Literal::Int(value))) => Ok(Literal::Int(-value)),
+			(true, TokenType::Number, Some(Literal::BigInt(value))) => Ok((-&*value).into()),
+			(true, TokenType::Number, Some(Literal::Number(value))) => Ok(Literal::Number(-value)),
+			(true, TokenType::Number, Some(Literal::Decimal(value))) => {
+				Ok(Literal::Decimal(Rc::new(-&*value)))
+			}
+			(
+				false,
+				TokenType::Number | TokenType::String | TokenType::Char | TokenType::True,
+				Some(literal),
+			) => Ok(literal),
+			_ => ParseError::token_mismatch(
+				self.previous(),
+				"Expected pattern, `_`, identifier, number, string, char, `true` or `false`",
+			),
+		}
+	}
+
+	fn loop_body(&mut self, kind: TokenType) -> Result<Block, ParseError> {
+		self.loops.push(kind);
+		let body = self.block();
+		self.loops.pop();
+		body
+	}
+
 	fn print_stmt(&mut self) -> Result<Stmt, ParseError> {
 		let expr = self.expression()?;
 		let next = self.advance();
@@ -147,6 +609,10 @@ impl Parser {
 	/// expr_stmt => expression (";" | EOF)
 	fn expr_stmt(&mut self) -> Result<Stmt, ParseError> {
 		let expr = self.expression()?;
+		// Might be the value of a block, which gets handled in `Parser::block`
+		if self.check(TokenType::RightBrace) {
+			return Ok(Stmt::Expr(expr));
+		}
 		let next = self.advance();
 		match next.typ {
 			TokenType::Semicolon | TokenType::Eof => Ok(Stmt::Expr(expr)),
@@ -159,19 +625,86 @@ impl Parser {
 		self.assignment()
 	}
 
-	/// assignment => identifier "=" (assignment | equality)
+	/// Expression right in front of a block, so `Name {` is not parsed as struct literal
+	fn condition(&mut self) -> Result<Expr, ParseError> {
+		self.with_struct_literals(false, Self::expression)
+	}
+
+	/// assignment => (IDENTIFIER | call "." IDENTIFIER | call "[" expression "]") ("=" | "+=" | "-=" | "*=" | "/=") assignment | range
 	fn assignment(&mut self) -> Result<Expr, ParseError> {
-		let expr = self.equality()?;
+		let expr = self.range()?;
 
-		if self.matches(TokenType::Equal) {
+		if self.matches_any(&[
+			TokenType::Equal,
+			TokenType::PlusEqual,
+			TokenType::MinusEqual,
+			TokenType::StarEqual,
+			TokenType::SlashEqual,
+		]) {
 			let equals = self.previous().clone();
 			let value = Box::new(self.assignment()?);
 
-			if let Expr::Var(name) = expr {
-				return Ok(Expr::Assign(name, value));
-			}
+			let target = match expr {
+				Expr::Var(name) => AssignTarget::Var(name),
+				Expr::Get(object, name) => AssignTarget::Field(object, name),
+				Expr::Index(object, index) => AssignTarget::Index(object, index),
+				_ => return Err(ParseError::InvalidAssignmentTarget(equals)),
+			};
+			return Ok(match equals.typ {
+				TokenType::PlusEqual => Expr::CompoundAssign(target, BinaryOp::Add, value),
+				TokenType::MinusEqual => Expr::CompoundAssign(target, BinaryOp::Sub, value),
+				TokenType::StarEqual => Expr::CompoundAssign(target, BinaryOp::Mul, value),
+				TokenType::SlashEqual => Expr::CompoundAssign(target, BinaryOp::Div, value),
+				_ => Expr::Assign(target, value),
+			});
+		}
 
-			return Err(ParseError::InvalidAssignmentTarget(equals));
+		Ok(expr)
+	}
+
+	/// range => logic_or (( ".." | "..=" ) logic_or ("step" logic_or)?)?
+	fn range(&mut self) -> Result<Expr, ParseError> {
+		let expr = self.logic_or()?;
+
+		if self.matches_any(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+			let inclusive = self.previous().typ == TokenType::DotDotEqual;
+			let end = Box::new(self.logic_or()?);
+			let step = if self.matches(TokenType::Step) {
+				Some(Box::new(self.logic_or()?))
+			} else {
+				None
+			};
+
+			return Ok(Expr::Range {
+				start: Box::new(expr),
+				end,
+				step,
+				inclusive,
+			});
+		}
+
+		Ok(expr)
+	}
+
+	/// logic_or => logic_and ("or" logic_and)*
+	fn logic_or(&mut self) -> Result<Expr, ParseError> {
+		let mut expr = self.logic_and()?;
+
+		while self.matches(TokenType::Or) {
+			let right = Box::new(self.logic_and()?);
+			expr = Expr::Logical(Box::new(expr), LogicalOp::Or, right);
+		}
+
+		Ok(expr)
+	}
+
+	/// logic_and => equality ("and" equality)*
+	fn logic_and(&mut self) -> Result<Expr, ParseError> {
+		let mut expr = self.equality()?;
+
+		while self.matches(TokenType::And) {
+			let right = Box::new(self.equality()?);
+			expr = Expr::Logical(Box::new(expr), LogicalOp::And, right);
 		}
 
 		Ok(expr)
@@ -195,9 +728,9 @@ impl Parser {
 		Ok(expr)
 	}
 
-	/// comparison => term ((">" | ">=" | "<" | "<=") term)*
+	/// comparison => bit_or ((">" | ">=" | "<" | "<=") bit_or)*
 	fn comparison(&mut self) -> Result<Expr, ParseError> {
-		let mut expr = self.term()?;
+		let mut expr = self.bit_or()?;
 
 		while self.matches_any(&[
 			TokenType::Greater,
@@ -206,7 +739,7 @@ impl Parser {
 			TokenType::LessEqual,
 		]) {
 			let typ = self.previous().typ;
-			let right = Box::new(self.term()?);
+			let right = Box::new(self.bit_or()?);
 
 			expr = Expr::Binary(
 				Box::new(expr),
@@ -223,6 +756,60 @@ impl Parser {
 		Ok(expr)
 	}
 
+	/// bit_or => bit_xor ("|" bit_xor)*
+	fn bit_or(&mut self) -> Result<Expr, ParseError> {
+		let mut expr = self.bit_xor()?;
+
+		while self.matches(TokenType::Pipe) {
+			let right = Box::new(self.bit_xor()?);
+			expr = Expr::Binary(Box::new(expr), BinaryOp::BitOr, right);
+		}
+
+		Ok(expr)
+	}
+
+	/// bit_xor => bit_and ("^" bit_and)*
+	fn bit_xor(&mut self) -> Result<Expr, ParseError> {
+		let mut expr = self.bit_and()?;
+
+		while self.matches(TokenType::Caret) {
+			let right = Box::new(self.bit_and()?);
+			expr = Expr::Binary(Box::new(expr), BinaryOp::BitXor, right);
+		}
+
+		Ok(expr)
+	}
+
+	/// bit_and => shift ("&" shift)*
+	fn bit_and(&mut self) -> Result<Expr, ParseError> {
+		let mut expr = self.shift()?;
+
+		while self.matches(TokenType::Ampersand) {
+			let right = Box::new(self.shift()?);
+			expr = Expr::Binary(Box::new(expr), BinaryOp::BitAnd, right);
+		}
+
+		Ok(expr)
+	}
+
+	/// shift => term (("<<" | ">>") term)*
+	fn shift(&mut self) -> Result<Expr, ParseError> {
+		let mut expr = self.term()?;
+
+		while self.matches_any(&[TokenType::LessLess, TokenType::GreaterGreater]) {
+			let typ = self.previous().typ;
+			let right = Box::new(self.term()?);
+
+			expr = if typ == TokenType::LessLess {
+				Expr::Binary(Box::new(expr), BinaryOp::Shl, right)
+			} else {
+				Expr::Binary(Box::new(expr), BinaryOp::Shr, right)
+			};
+		}
+
+		Ok(expr)
+	}
+
 	/// term => factor (("+" | "-") factor)*
 	fn term(&mut self) -> Result<Expr, ParseError> {
 		let mut expr = self.factor()?;
@@ -241,47 +828,98 @@ impl Parser {
 		Ok(expr)
 	}
 
-	/// factor => unary (("/" | "*") unary)*
+	/// factor => unary (("/" | "~/" | "*" | "%") unary)*
 	fn factor(&mut self) -> Result<Expr, ParseError> {
 		let mut expr = self.unary()?;
 
-		while self.matches_any(&[TokenType::Star, TokenType::Slash]) {
-			let typ = self.previous().typ;
+		while self.matches_any(&[
+			TokenType::Star,
+			TokenType::Slash,
+			TokenType::TildeSlash,
+			TokenType::Percent,
+		]) {
+			let op = match self.previous().typ {
+				TokenType::Star => BinaryOp::Mul,
+				TokenType::Slash => BinaryOp::Div,
+				TokenType::TildeSlash => BinaryOp::FloorDiv,
+				_ => BinaryOp::Rem,
+			};
 			let right = Box::new(self.unary()?);
 
-			expr = if typ == TokenType::Star {
-				Expr::Binary(Box::new(expr), BinaryOp::Mul, right)
-			} else {
-				Expr::Binary(Box::new(expr), BinaryOp::Div, right)
-			};
+			expr = Expr::Binary(Box::new(expr), op, right);
 		}
 
 		Ok(expr)
 	}
 
-	/// unary => ("!" | "-") unary
+	/// unary => ("!" | "-" | "~") unary | power
 	fn unary(&mut self) -> Result<Expr, ParseError> {
-		if self.matches_any(&[TokenType::Bang, TokenType::Minus]) {
-			let typ = self.previous().typ;
+		if self.matches_any(&[TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
+			let op = match self.previous().typ {
+				TokenType::Bang => UnaryOp::Not,
+				TokenType::Minus => UnaryOp::Neg,
+				_ => UnaryOp::BitNot,
+			};
 			let right = Box::new(self.unary()?);
 
-			return if typ == TokenType::Bang {
-				Ok(Expr::Unary(UnaryOp::Not, right))
-			} else {
-				Ok(Expr::Unary(UnaryOp::Neg, right))
-			};
+			return Ok(Expr::Unary(op, right));
 		}
 
-		self.primary()
+		self.power()
 	}
 
-	/// primary => "(" expression ")", IDENTIFIER, NUMBER | STRING | "true" | "false"
+	/// power => call ("**" unary)?
+	///
+	/// Right associative and binds tighter than unary operators on its left,
+	/// `-2 ** 2` is `-(2 ** 2)`, `2 ** -1` is allowed
+	fn power(&mut self) -> Result<Expr, ParseError> {
+		let expr = self.call()?;
+
+		if self.matches(TokenType::StarStar) {
+			let right = Box::new(self.unary()?);
+			return Ok(Expr::Binary(Box::new(expr), BinaryOp::Pow, right));
+		}
+
+		Ok(expr)
+	}
+
+	/// call => primary ("(" arguments? ")" | "." IDENTIFIER | "[" expression "]")*
+	/// arguments => expression ("," expression)* ","?
+	fn call(&mut self) -> Result<Expr, ParseError> {
+		let mut expr = self.primary()?;
+
+		loop {
+			if self.matches(TokenType::LeftParen) {
+				let args = self.with_struct_literals(true, |this| {
+					this.list(TokenType::RightParen, Self::expression)
+				})?;
+				expr = Expr::Call(Box::new(expr), args);
+			} else if self.matches(TokenType::Dot) {
+				let name = self
+					.consume(TokenType::Identifier, "Expected field name after `.`")?
+					.lexeme
+					.clone();
+				expr = Expr::Get(Box::new(expr), name);
+			} else if self.matches(TokenType::LeftBracket) {
+				let index = self.with_struct_literals(true, Self::expression)?;
+				self.consume(TokenType::RightBracket, "Expected closing `]`")?;
+				expr = Expr::Index(Box::new(expr), Box::new(index));
+			} else {
+				break Ok(expr);
+			}
+		}
+	}
+
+	/// primary => "(" expression ")" | "(" expression "," (expression ("," expression)* ","?)? ")" | "[" (expression ("," expression)* ","?)? "]"
+	///   | "#{" (map_entry ("," map_entry)* ","?)? "}" | block_like | break | "continue" | return
+	///   | "this" | "super" "." IDENTIFIER | struct_literal | IDENTIFIER "::" IDENTIFIER | IDENTIFIER | NUMBER | STRING | "true" | "false"
 	fn primary(&mut self) -> Result<Expr, ParseError> {
 		if self.matches_any(&[
 			TokenType::True,
 			TokenType::False,
 			TokenType::Number,
 			TokenType::String,
+			TokenType::Char,
 		]) {
 			return Ok(Expr::Literal(
 				self
@@ -293,13 +931,102 @@ impl Parser {
 		}
 
 		if self.matches(TokenType::LeftParen) {
-			let expr = Box::new(self.expression()?);
+			if self.matches(TokenType::RightParen) {
+				return Ok(Expr::Literal(Literal::Unit));
+			}
+			let expr = self.with_struct_literals(true, Self::expression)?;
+			// A comma turns the group into a tuple, `(1,)` has a single value
+			if self.matches(TokenType::Comma) {
+				let mut items = vec![expr];
+				items.extend(self.with_struct_literals(true, |this| {
+					this.list(TokenType::RightParen, Self::expression)
+				})?);
+				return Ok(Expr::Tuple(items));
+			}
 			self.consume(TokenType::RightParen, "Expected closing `)`")?;
-			return Ok(Expr::Group(expr));
+			return Ok(Expr::Group(Box::new(expr)));
+		}
+
+		if self.matches(TokenType::StringHead) {
+			return self.interpolation();
+		}
+
+		if self.matches(TokenType::LeftBracket) {
+			let items = self.with_struct_literals(true, |this| {
+				this.list(TokenType::RightBracket, Self::expression)
+			})?;
+			return Ok(Expr::List(items));
+		}
+
+		if self.matches(TokenType::HashLeftBrace) {
+			let entries = self.with_struct_literals(true, |this| {
+				this.list(TokenType::RightBrace, Self::map_entry)
+			})?;
+			return Ok(Expr::Map(entries));
+		}
+
+		if self.check_block_like() {
+			return self.block_like();
+		}
+
+		if self.matches(TokenType::Break) {
+			return self.break_expr();
+		}
+
+		if self.matches(TokenType::Continue) {
+			if self.loops.is_empty() {
+				self.errors.push(ParseError::Misplaced(
+					self.previous().clone(),
+					"`continue` outside of a loop".into(),
+				));
+			}
+			return Ok(Expr::Continue);
+		}
+
+		if self.matches(TokenType::Return) {
+			let token = self.previous().clone();
+			let value = self.optional_value()?;
+			if !self.in_function {
+				self.errors.push(ParseError::Misplaced(
+					token,
+					"`return` outside of a function".into(),
+				));
+			}
+			return Ok(Expr::Return(value));
+		}
+
+		if self.matches(TokenType::This) {
+			if !self.in_impl {
+				self.errors.push(ParseError::Misplaced(
+					self.previous().clone(),
+					"`this` outside of an `impl` block".into(),
+				));
+			}
+			return Ok(Expr::This);
+		}
+
+		if self.matches(TokenType::Super) {
+			if !self.in_impl {
+				self.errors.push(ParseError::Misplaced(
+					self.previous().clone(),
+					"`super` outside of an `impl` block".into(),
+				));
+			}
+			self.consume(TokenType::Dot, "Expected `.` after `super`")?;
+			let name = self.consume(TokenType::Identifier, "Expected method name")?;
+			return Ok(Expr::Super(name.lexeme.clone()));
 		}
 
 		if self.matches(TokenType::Identifier) {
-			return Ok(Expr::Var(self.previous().lexeme.clone()));
+			let name = self.previous().lexeme.clone();
+			if self.struct_literals && self.matches(TokenType::LeftBrace) {
+				return self.struct_literal(name);
+			}
+			if self.matches(TokenType::ColonColon) {
+				let variant = self.consume(TokenType::Identifier, "Expected variant name after `::`")?;
+				return Ok(Expr::Path(name, variant.lexeme.clone()));
+			}
+			return Ok(Expr::Var(name));
 		}
 
 		ParseError::token_mismatch(
@@ -307,10 +1034,153 @@ impl Parser {
 			"Expected expression, identifier, number, string, `true` or `false`",
 		)
 	}
+
+	/// map_entry => (IDENTIFIER | expression) ":" expression
+	///
+	/// Bare identifiers are string keys, `(name): value` uses the value of a variable
+	fn map_entry(&mut self) -> Result<(Expr, Expr), ParseError> {
+		let key = if self.check(TokenType::Identifier) && self.check_next(TokenType::Colon) {
+			let name = self.advance().lexeme.clone();
+			Expr::Literal(Literal::String(name))
+		} else {
+			self.expression()?
+		};
+		self.consume(TokenType::Colon, "Expected `:` after map key")?;
+		Ok((key, self.expression()?))
+	}
+
+	/// interpolation => STRING_HEAD expression (STRING_MIDDLE expression)* STRING_TAIL
+	fn interpolation(&mut self) -> Result<Expr, ParseError> {
+		let mut parts = vec![Expr::Literal(
+			self
+				.previous()
+				.literal
+				.clone()
+				.expect("String has no value!"),
+		)];
+
+		loop {
+			parts.push(self.with_struct_literals(true, Self::expression)?);
+
+			if !self.matches_any(&[TokenType::StringMiddle, TokenType::StringTail]) {
+				return ParseError::token_mismatch(
+					self.peek(),
+					"Expected `}` after interpolated expression",
+				);
+			}
+			let token = self.previous();
+			parts.push(Expr::Literal(
+				token.literal.clone().expect("String has no value!"),
+			));
+			if token.typ == TokenType::StringTail {
+				return Ok(Expr::Interpolation(parts));
+			}
+		}
+	}
+
+	/// struct_literal => IDENTIFIER "{" (field ("," field)* ","?)? "}"
+	/// field => IDENTIFIER (":" expression)?
+	fn struct_literal(&mut self, name: String) -> Result<Expr, ParseError> {
+		let fields = self.list(TokenType::RightBrace, |this| {
+			let field = this
+				.consume(TokenType::Identifier, "Expected field name")?
+				.lexeme
+				.clone();
+			// Shorthand `Name { field }` for `Name { field: field }`
+			let value = if this.matches(TokenType::Colon) {
+				this.expression()?
+			} else {
+				Expr::Var(field.clone())
+			};
+			Ok((field, value))
+		})?;
+
+		Ok(Expr::StructLiteral { name, fields })
+	}
+
+	/// break => "break" expression?
+	fn break_expr(&mut self) -> Result<Expr, ParseError> {
+		let token = self.previous().clone();
+		let value = self.optional_value()?;
+
+		// The surrounding statement is still well formed,
+		// so the error gets recorded without aborting it
+		match self.loops.last() {
+			None => self.errors.push(ParseError::Misplaced(
+				token,
+				"`break` outside of a loop".into(),
+			)),
+			Some(TokenType::Loop) => {}
+			Some(_) if value.is_some() => self.errors.push(ParseError::Misplaced(
+				token,
+				"`break` with a value is only allowed inside of `loop`".into(),
+			)),
+			Some(_) => {}
+		}
+
+		Ok(Expr::Break(value))
+	}
+
+	/// Value of `break` or `return`, which is missing if the statement or surrounding expression
+	/// ends right away, like a match arm followed by `,`
+	fn optional_value(&mut self) -> Result<Option<Box<Expr>>, ParseError> {
+		let ends = [
+			TokenType::Semicolon,
+			TokenType::Comma,
+			TokenType::RightBrace,
+			TokenType::RightParen,
+			TokenType::RightBracket,
+		];
+		if ends.iter().any(|end| self.check(*end)) || self.is_at_end() {
+			return Ok(None);
+		}
+		Ok(Some(Box::new(self.expression()?)))
+	}
 }
 
 /// Utility methods
 impl Parser {
+	/// Parses comma separated items with an optional trailing comma, until and including `end`
+	fn list<T>(
+		&mut self,
+		end: TokenType,
+		mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+	) -> Result<Vec<T>, ParseError> {
+		let mut items = Vec::new();
+		while !self.check(end) {
+			items.push(item(self)?);
+			if !self.matches(TokenType::Comma) {
+				break;
+			}
+		}
+		self.consume(end, "Expected `,` or closing delimiter")?;
+		Ok(items)
+	}
+
+	fn with_struct_literals<T>(
+		&mut self,
+		allowed: bool,
+		f: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+	) -> Result<T, ParseError> {
+		let previous = std::mem::replace(&mut self.struct_literals, allowed);
+		let result = f(self);
+		self.struct_literals = previous;
+		result
+	}
+
+	fn check_block_like(&self) -> bool {
+		[
+			TokenType::LeftBrace,
+			TokenType::If,
+			TokenType::While,
+			TokenType::Loop,
+			TokenType::For,
+			TokenType::Match,
+		]
+		.iter()
+		.any(|typ| self.check(*typ))
+	}
+
 	fn consume(&mut self, until: TokenType, error_msg: &str) -> Result<&Token, ParseError> {
 		if self.check(until) {
 			return Ok(self.advance());
@@ -345,6 +1215,14 @@ impl Parser {
 		self.peek().typ == typ
 	}
 
+	/// Like `check`, but looks at the token after the next one
+	fn check_next(&self, typ: TokenType) -> bool {
+		match self.tokens.get(self.current + 1) {
+			Some(token) => token.typ == typ,
+			None => false,
+		}
+	}
+
 	fn advance(&mut self) -> &Token {
 		if !self.is_at_end() {
 			self.current += 1;
@@ -8,37 +8,70 @@ pub enum TokenType {
 	RightParen,
 	LeftBrace,
 	RightBrace,
+	LeftBracket,
+	/// `#{`, starts a map literal
+	HashLeftBrace,
+	RightBracket,
 	Comma,
+	Pipe,
+	Ampersand,
+	Caret,
+	Tilde,
+	Colon,
+	ColonColon,
 	Dot,
 	Minus,
 	Plus,
 	Slash,
 	Star,
+	Percent,
 	Semicolon,
 	NewLine,
 
 	// One or two character tokens.
 	Bang,
 	BangEqual,
+	PlusEqual,
+	MinusEqual,
+	StarEqual,
+	SlashEqual,
 	Equal,
 	EqualEqual,
+	FatArrow,
 	Greater,
 	GreaterEqual,
 	Less,
 	LessEqual,
+	LessLess,
+	GreaterGreater,
+	DotDot,
+	DotDotEqual,
+	TildeSlash,
+	StarStar,
 
 	// Literals.
 	Identifier,
 	String,
+	/// Text before the first interpolation of a string, `"a {` in `"a {b} c"`
+	StringHead,
+	/// Text between two interpolations
+	StringMiddle,
+	/// Text after the last interpolation, `} c"` in `"a {b} c"`
+	StringTail,
+	Char,
 	Number,
 
 	// Keywords.
 	And,
 	Struct,
+	Enum,
+	Impl,
 	Else,
 	False,
 	Fun,
 	For,
+	In,
+	Step,
 	If,
 	Or,
 	Print,
@@ -48,6 +81,9 @@ pub enum TokenType {
 	True,
 	While,
 	Loop,
+	Match,
+	Break,
+	Continue,
 	Const,
 	Mut,
 